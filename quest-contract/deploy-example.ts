@@ -12,11 +12,13 @@ import { Uint128 } from "cosmwasm";
 const RPC_ENDPOINT = "https://rpc.juno.network";
 const CHAIN_ID = "juno-1";
 const MNEMONIC = "YOUR_MNEMONIC_HERE";
+const DENOM = "ujuno";
 
 // ============= HELPER TYPES =============
 interface InstantiateMsg {
   owner?: string;
-  quest_creation_fee: string;
+  denom: string;
+  quest_creation_fee?: string;
   initial_balance: string;
 }
 
@@ -28,9 +30,17 @@ interface CreateQuestMsg {
   };
 }
 
-interface CompleteQuestMsg {
-  complete_quest: {
-    quest_id: string;
+interface SubmitCompletionMsg {
+  submit_completion: {
+    quest_id: number;
+    proof: string;
+  };
+}
+
+interface ApproveSubmissionMsg {
+  approve_submission: {
+    quest_id: number;
+    submitter: string;
   };
 }
 
@@ -97,8 +107,9 @@ class QuestContractManager {
 
     const initMsg: InstantiateMsg = {
       owner: undefined, // Будет установлен на адрес отправителя
+      denom: DENOM, // Монета, которой обеспечены токены контракта
       quest_creation_fee: "5", // 5 токенов
-      initial_balance: "1000", // 1000 токенов для инициализации
+      initial_balance: "1000", // 1000 токенов, оплачиваются приложенными монетами
     };
 
     const result = await this.client.instantiate(
@@ -106,7 +117,8 @@ class QuestContractManager {
       codeId,
       initMsg,
       label,
-      "auto"
+      "auto",
+      { funds: [{ denom: DENOM, amount: initMsg.initial_balance }] }
     );
 
     this.contractAddress = result.contractAddress;
@@ -152,17 +164,48 @@ class QuestContractManager {
   }
 
   /**
-   * Выполняем квест (получаем награду)
+   * Отправляем заявку о выполнении квеста (награду выплатит создатель)
+   */
+  async submitCompletion(questId: number, proof: string): Promise<string> {
+    if (!this.client) throw new Error("Client not initialized");
+    if (!this.contractAddress) throw new Error("Contract not instantiated");
+
+    console.log(`\n📨 Заявка на выполнение квеста ID: ${questId}`);
+
+    const msg: SubmitCompletionMsg = {
+      submit_completion: {
+        quest_id: questId,
+        proof,
+      },
+    };
+
+    const result = await this.client.execute(
+      this.userAddress,
+      this.contractAddress,
+      msg,
+      "auto"
+    );
+
+    console.log("✅ Заявка отправлена!");
+    console.log("📝 TX Hash:", result.transactionHash);
+    console.log("📊 Logs:", result.logs);
+
+    return result.transactionHash;
+  }
+
+  /**
+   * Одобряем заявку (создатель квеста, исполнитель получает награду)
    */
-  async completeQuest(questId: number): Promise<string> {
+  async approveSubmission(questId: number, submitter: string): Promise<string> {
     if (!this.client) throw new Error("Client not initialized");
     if (!this.contractAddress) throw new Error("Contract not instantiated");
 
-    console.log(`\n✅ Выполнение квеста ID: ${questId}`);
+    console.log(`\n✅ Одобрение заявки ${submitter} по квесту ID: ${questId}`);
 
-    const msg: CompleteQuestMsg = {
-      complete_quest: {
-        quest_id: questId.toString(),
+    const msg: ApproveSubmissionMsg = {
+      approve_submission: {
+        quest_id: questId,
+        submitter,
       },
     };
 
@@ -260,7 +303,7 @@ class QuestContractManager {
       console.log(`    Название: ${quest.name}`);
       console.log(`    Описание: ${quest.description}`);
       console.log(`    Награда: ${quest.reward_amount} токенов`);
-      console.log(`    Выполнен: ${quest.completed ? "Да" : "Нет"}`);
+      console.log(`    Статус: ${quest.status}`);
       console.log(`    Создатель: ${quest.creator}`);
     });
 
@@ -309,7 +352,8 @@ class QuestContractManager {
 
     console.log("📊 Конфиг:");
     console.log(`  Владелец: ${result.owner}`);
-    console.log(`  Комиссия за создание: ${result.quest_creation_fee} токенов`);
+    console.log(`  Монета: ${result.denom}`);
+    console.log(`  Модель комиссии: ${JSON.stringify(result.fee_model)}`);
     console.log(`  Всего квестов: ${result.total_quests}`);
     console.log(`  Выполнено квестов: ${result.total_completed}`);
 
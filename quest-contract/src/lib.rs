@@ -11,23 +11,354 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
-    Order, Addr, StdError,
+    from_json, to_json_binary, to_json_string, Binary, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, Uint128, Order, Addr, StdError, Event, BankMsg, WasmMsg, CosmosMsg, Empty, Reply,
+    SubMsg, SubMsgResult, coins,
 };
-use cw_storage_plus::{Item, Map};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use cw_utils::{must_pay, nonpayable, one_coin, Expiration, PaymentError};
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
+use thiserror::Error;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;  // ✅ ТОЛЬКО ИЗ std!
 
 
+const CONTRACT_NAME: &str = "crates.io:quest-contract";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+
+// ============= ERRORS =============
+
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("[{}] {0}", self.code())]
+    Std(#[from] StdError),
+
+    #[error("[{}] {0}", self.code())]
+    Payment(#[from] PaymentError),
+
+    #[error("[{}] Unauthorized", self.code())]
+    Unauthorized {},
+
+    #[error("[{}] {field} must be a valid number", self.code())]
+    InvalidAmount { field: String },
+
+    #[error("[{}] Amount must be greater than zero", self.code())]
+    ZeroAmount {},
+
+    #[error("[{}] Invalid input: {reason}", self.code())]
+    InvalidInput { reason: String },
+
+    #[error("[{}] {field} must not exceed {max} characters", self.code())]
+    ContentTooLong { field: String, max: u32 },
+
+    #[error("[{}] Insufficient balance: required {required}, available {available}", self.code())]
+    InsufficientBalance { required: Uint128, available: Uint128 },
+
+    #[error("[{}] Insufficient allowance: required {required}, available {available}", self.code())]
+    InsufficientAllowance { required: Uint128, available: Uint128 },
+
+    #[error("[{}] Allowance has expired", self.code())]
+    AllowanceExpired {},
+
+    #[error("[{}] Cannot set an allowance to your own account", self.code())]
+    CannotSetOwnAccount {},
+
+    #[error("[{}] Quest reward is not covered by escrow", self.code())]
+    InsufficientEscrow {},
+
+    #[error("[{}] Attached funds must equal the escrowed reward of {expected}", self.code())]
+    FundsMismatch { expected: Uint128 },
+
+    #[error("[{}] Quest {quest_id} not found", self.code())]
+    QuestNotFound { quest_id: u64 },
+
+    #[error("[{}] Quest is already completed", self.code())]
+    QuestAlreadyCompleted {},
+
+    #[error("[{}] Cannot complete your own quest", self.code())]
+    CannotCompleteOwnQuest {},
+
+    #[error("[{}] Quest is not open", self.code())]
+    QuestNotActive {},
+
+    #[error("[{}] Quest deadline has passed", self.code())]
+    QuestExpired {},
+
+    #[error("[{}] Quest is frozen by an open dispute", self.code())]
+    QuestDisputed {},
+
+    #[error("[{}] Deadline must be in the future", self.code())]
+    DeadlineInPast {},
+
+    #[error("[{}] Address has already completed this quest", self.code())]
+    AlreadyCompleted {},
+
+    #[error("[{}] Reputation {actual} is below the required {required}", self.code())]
+    InsufficientReputation { required: i64, actual: i64 },
+
+    #[error("[{}] Submission not found", self.code())]
+    SubmissionNotFound {},
+
+    #[error("[{}] Submission has already been reviewed", self.code())]
+    SubmissionAlreadyReviewed {},
+
+    #[error("[{}] A submission for this quest already exists", self.code())]
+    DuplicateSubmission {},
+
+    #[error("[{}] Review period is still running until {ends_at}", self.code())]
+    ReviewPeriodActive { ends_at: u64 },
+
+    #[error("[{}] Review period has ended", self.code())]
+    ReviewPeriodEnded {},
+
+    #[error("[{}] Only rejected submissions can be disputed", self.code())]
+    SubmissionNotRejected {},
+
+    #[error("[{}] Dispute window for this rejection has closed", self.code())]
+    DisputeWindowClosed {},
+
+    #[error("[{}] Dispute not found", self.code())]
+    DisputeNotFound {},
+
+    #[error("[{}] Dispute already exists for this submission", self.code())]
+    DisputeExists {},
+
+    #[error("[{}] Dispute is already resolved", self.code())]
+    DisputeAlreadyResolved {},
+
+    #[error("[{}] Rejected submissions can still be disputed until {ends_at}", self.code())]
+    DisputeWindowOpen { ends_at: u64 },
+
+    #[error("[{}] No ownership transfer is pending", self.code())]
+    NoPendingOwner {},
+
+    #[error("[{}] Ownership proposal has expired", self.code())]
+    OwnershipProposalExpired {},
+
+    #[error("[{}] Category {tag} not found", self.code())]
+    CategoryNotFound { tag: String },
+
+    #[error("[{}] Category {tag} already exists", self.code())]
+    CategoryExists { tag: String },
+
+    #[error("[{}] Category {tag} is retired", self.code())]
+    CategoryRetired { tag: String },
+
+    #[error("[{}] A quest can have at most {max} tags", self.code())]
+    TooManyTags { max: u32 },
+
+    #[error("[{}] Referrer is already registered", self.code())]
+    ReferrerAlreadySet {},
+
+    #[error("[{}] Cannot refer yourself", self.code())]
+    SelfReferral {},
+
+    #[error("[{}] Referrer was referred by you", self.code())]
+    ReferralCycle {},
+
+    #[error("[{}] Only users without completed quests can register a referrer", self.code())]
+    NotNewUser {},
+
+    #[error("[{}] Cannot migrate from contract {contract}", self.code())]
+    WrongContract { contract: String },
+
+    #[error("[{}] Cannot downgrade from {stored} to {current}", self.code())]
+    CannotDowngrade { stored: String, current: String },
+}
+
+
+impl ContractError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ContractError::Std(_) => "E000",
+            ContractError::Payment(_) => "E001",
+            ContractError::Unauthorized {} => "E100",
+            ContractError::InvalidAmount { .. } => "E101",
+            ContractError::ZeroAmount {} => "E102",
+            ContractError::InvalidInput { .. } => "E103",
+            ContractError::ContentTooLong { .. } => "E104",
+            ContractError::InsufficientBalance { .. } => "E200",
+            ContractError::InsufficientEscrow {} => "E201",
+            ContractError::FundsMismatch { .. } => "E202",
+            ContractError::InsufficientAllowance { .. } => "E203",
+            ContractError::AllowanceExpired {} => "E204",
+            ContractError::CannotSetOwnAccount {} => "E205",
+            ContractError::QuestNotFound { .. } => "E300",
+            ContractError::QuestAlreadyCompleted {} => "E301",
+            ContractError::CannotCompleteOwnQuest {} => "E302",
+            ContractError::QuestNotActive {} => "E303",
+            ContractError::QuestExpired {} => "E304",
+            ContractError::QuestDisputed {} => "E305",
+            ContractError::DeadlineInPast {} => "E306",
+            ContractError::AlreadyCompleted {} => "E307",
+            ContractError::InsufficientReputation { .. } => "E308",
+            ContractError::SubmissionNotFound {} => "E400",
+            ContractError::SubmissionAlreadyReviewed {} => "E401",
+            ContractError::DuplicateSubmission {} => "E402",
+            ContractError::ReviewPeriodActive { .. } => "E403",
+            ContractError::ReviewPeriodEnded {} => "E404",
+            ContractError::SubmissionNotRejected {} => "E405",
+            ContractError::DisputeWindowClosed {} => "E406",
+            ContractError::DisputeNotFound {} => "E500",
+            ContractError::DisputeExists {} => "E501",
+            ContractError::DisputeAlreadyResolved {} => "E502",
+            ContractError::DisputeWindowOpen { .. } => "E503",
+            ContractError::NoPendingOwner {} => "E600",
+            ContractError::OwnershipProposalExpired {} => "E601",
+            ContractError::CategoryNotFound { .. } => "E800",
+            ContractError::CategoryExists { .. } => "E801",
+            ContractError::CategoryRetired { .. } => "E802",
+            ContractError::TooManyTags { .. } => "E803",
+            ContractError::ReferrerAlreadySet {} => "E900",
+            ContractError::SelfReferral {} => "E901",
+            ContractError::ReferralCycle {} => "E902",
+            ContractError::NotNewUser {} => "E903",
+            ContractError::WrongContract { .. } => "E700",
+            ContractError::CannotDowngrade { .. } => "E701",
+        }
+    }
+}
+
+
 // ============= STATE STRUCTURES =============
 
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Config {
-    pub owner: Addr,
-    pub quest_creation_fee: Uint128,
+    pub owner: Option<Addr>,
+    pub arbiter: Addr,
+    pub treasury: Addr,
+    pub badge_nft_contract: Option<Addr>,
+    pub denom: String,
+    pub fee_model: FeeModel,
+    pub review_period: u64,
+    pub max_name_length: u32,
+    pub max_description_length: u32,
+    pub referral_bonus_bps: u16,
     pub total_quests: u64,
     pub total_completed: u64,
+    pub total_fees_collected: Uint128,
+}
+
+
+/// How the quest creation fee is derived from the total reward a quest escrows
+/// (`reward_amount * max_completions`).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeModel {
+    Flat {
+        amount: Uint128,
+    },
+    Percentage {
+        bps: u16,
+        min: Uint128,
+        max: Option<Uint128>,
+    },
+    /// Tiers sorted by `min_reward`; the last tier whose `min_reward` is reached applies.
+    Tiered {
+        tiers: Vec<FeeTier>,
+    },
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FeeTier {
+    pub min_reward: Uint128,
+    pub fee: Uint128,
+}
+
+
+impl FeeModel {
+    fn validate(&self) -> Result<(), ContractError> {
+        match self {
+            FeeModel::Flat { .. } => Ok(()),
+            FeeModel::Percentage { bps, min, max } => {
+                ensure_valid_bps(*bps, "bps")?;
+                if max.is_some_and(|max| max < *min) {
+                    return Err(ContractError::InvalidInput {
+                        reason: "max fee must not be below min fee".to_string(),
+                    });
+                }
+                Ok(())
+            }
+            FeeModel::Tiered { tiers } => {
+                if tiers.first().is_none_or(|tier| !tier.min_reward.is_zero()) {
+                    return Err(ContractError::InvalidInput {
+                        reason: "fee tiers must start at min_reward 0".to_string(),
+                    });
+                }
+                if tiers.windows(2).any(|pair| pair[0].min_reward >= pair[1].min_reward) {
+                    return Err(ContractError::InvalidInput {
+                        reason: "fee tiers must be sorted by min_reward".to_string(),
+                    });
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn fee_for(&self, reward: Uint128) -> Uint128 {
+        match self {
+            FeeModel::Flat { amount } => *amount,
+            FeeModel::Percentage { bps, min, max } => {
+                let fee = reward.multiply_ratio(*bps, MAX_BPS).max(*min);
+                max.map_or(fee, |max| fee.min(max))
+            }
+            FeeModel::Tiered { tiers } => tiers
+                .iter()
+                .rev()
+                .find(|tier| reward >= tier.min_reward)
+                .map_or(Uint128::zero(), |tier| tier.fee),
+        }
+    }
+
+    /// Fee for a quest escrowing `escrow` of `asset`. The fee is always charged
+    /// in internal tokens, so quests funded in other assets can't be measured
+    /// against the model and pay its base fee (the fee for a zero reward).
+    fn fee_for_asset(&self, asset: &Asset, escrow: Uint128) -> Uint128 {
+        match asset {
+            Asset::Internal => self.fee_for(escrow),
+            Asset::Native { .. } | Asset::Cw20 { .. } => self.fee_for(Uint128::zero()),
+        }
+    }
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestStatus {
+    Open,
+    Submitted,
+    Disputed,
+    Completed,
+    Cancelled,
+    Expired,
+}
+
+
+impl QuestStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            QuestStatus::Open => "open",
+            QuestStatus::Submitted => "submitted",
+            QuestStatus::Disputed => "disputed",
+            QuestStatus::Completed => "completed",
+            QuestStatus::Cancelled => "cancelled",
+            QuestStatus::Expired => "expired",
+        }
+    }
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Asset {
+    Internal,
+    Native { denom: String },
+    Cw20 { contract: Addr },
 }
 
 
@@ -37,113 +368,116 @@ pub struct Quest {
     pub creator: Addr,
     pub name: String,
     pub description: String,
+    pub reward_asset: Asset,
     pub reward_amount: Uint128,
-    pub completed: bool,
+    pub max_completions: u64,
+    pub completions: u64,
+    pub status: QuestStatus,
     pub completed_by: Option<Addr>,
     pub created_at: u64,
     pub completed_at: Option<u64>,
+    pub deadline: Option<u64>,
+    pub tags: Vec<String>,
+    pub min_reputation: Option<i64>,
+    pub badge: Option<BadgeTemplate>,
 }
 
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
-pub struct UserBalance {
-    pub address: Addr,
-    pub balance: Uint128,
-    pub total_earned: Uint128,
-    pub quests_created: u64,
-    pub quests_completed: u64,
+pub struct BadgeTemplate {
+    pub name: String,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub token_uri: Option<String>,
 }
 
 
-// ============= STORAGE =============
-
-
-const CONFIG: Item<Config> = Item::new("config");
-const BALANCES: Map<&Addr, UserBalance> = Map::new("balances");
-const QUESTS: Map<u64, Quest> = Map::new("quests");
-const USER_QUESTS: Map<&Addr, Vec<u64>> = Map::new("user_quests");
-
-
-// ============= MESSAGES =============
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Completion {
+    pub quest_id: u64,
+    pub completer: Addr,
+    pub completed_at: u64,
+}
 
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
-pub struct InstantiateMsg {
-    pub owner: Option<String>,
-    pub quest_creation_fee: String,
-    pub initial_balance: String,
+pub enum SubmissionStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Closed,
 }
 
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-#[serde(rename_all = "snake_case")]
-pub enum ExecuteMsg {
-    CreateQuest {
-        name: String,
-        description: String,
-        reward_amount: String,
-    },
-    CompleteQuest {
-        quest_id: u64,
-    },
-    Transfer {
-        recipient: String,
-        amount: String,
-    },
-    AdminWithdraw {
-        amount: String,
-    },
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Submission {
+    pub quest_id: u64,
+    pub submitter: Addr,
+    pub proof: String,
+    pub status: SubmissionStatus,
+    pub submitted_at: u64,
+    pub reviewed_at: Option<u64>,
+    pub rejection_reason: Option<String>,
 }
 
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
-pub enum QueryMsg {
-    GetConfig {},
-    GetBalance { address: String },
-    GetQuest { quest_id: u64 },
-    GetActiveQuests {},
-    GetUserQuests { address: String },
-    GetUserStats { address: String },
+pub enum DisputeStatus {
+    Open,
+    Resolved,
 }
 
 
-// ============= RESPONSE TYPES =============
+impl DisputeStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DisputeStatus::Open => "open",
+            DisputeStatus::Resolved => "resolved",
+        }
+    }
+}
 
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct GetConfigResponse {
-    pub owner: Addr,
-    pub quest_creation_fee: Uint128,
-    pub total_quests: u64,
-    pub total_completed: u64,
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeParty {
+    Submitter,
+    Creator,
 }
 
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct GetBalanceResponse {
-    pub address: Addr,
-    pub balance: Uint128,
-    pub total_earned: Uint128,
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Dispute {
+    pub quest_id: u64,
+    pub submitter: Addr,
+    pub reason: String,
+    pub status: DisputeStatus,
+    pub winner: Option<DisputeParty>,
+    pub opened_at: u64,
+    pub resolved_at: Option<u64>,
 }
 
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct GetQuestResponse {
-    pub quest: Quest,
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingOwner {
+    pub owner: Addr,
+    pub expiry: Option<Expiration>,
 }
 
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct GetActiveQuestsResponse {
-    pub quests: Vec<Quest>,
-    pub count: u64,
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TokenInfo {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub total_supply: Uint128,
 }
 
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct GetUserStatsResponse {
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct UserBalance {
     pub address: Addr,
     pub balance: Uint128,
     pub total_earned: Uint128,
@@ -152,427 +486,5735 @@ pub struct GetUserStatsResponse {
 }
 
 
-// ============= HELPER FUNCTION =============
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Reputation {
+    pub score: i64,
+    pub approved: u64,
+    pub rejected: u64,
+    pub disputes_lost: u64,
+    pub prompt_payouts: u64,
+}
 
 
-fn get_or_create_balance(
-    storage: &dyn cosmwasm_std::Storage,
-    address: &Addr,
-) -> StdResult<UserBalance> {
-    Ok(BALANCES
-        .may_load(storage, address)?
-        .unwrap_or(UserBalance {
-            address: address.clone(),
-            balance: Uint128::zero(),
-            total_earned: Uint128::zero(),
-            quests_created: 0,
-            quests_completed: 0,
-        }))
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct ReferralStats {
+    pub referrals: u64,
+    pub total_earned: Uint128,
 }
 
 
-// ============= ENTRY POINTS =============
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Category {
+    pub tag: String,
+    pub active: bool,
+    pub created_at: u64,
+}
 
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn instantiate(
-    deps: DepsMut,
-    _env: Env,
-    info: MessageInfo,
-    msg: InstantiateMsg,
-) -> StdResult<Response> {
-    let owner = if let Some(owner_addr) = msg.owner {
-        deps.api.addr_validate(&owner_addr)?
-    } else {
-        info.sender.clone()
-    };
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Allowance {
+    pub allowance: Uint128,
+    pub expires: Expiration,
+}
 
-    let fee = Uint128::from_str(&msg.quest_creation_fee)
-        .map_err(|_| StdError::generic_err("quest_creation_fee must be a valid number"))?;
-    
-    let balance = Uint128::from_str(&msg.initial_balance)
-        .map_err(|_| StdError::generic_err("initial_balance must be a valid number"))?;
 
-    let config = Config {
-        owner: owner.clone(),
-        quest_creation_fee: fee,
-        total_quests: 0,
-        total_completed: 0,
-    };
+// ============= STORAGE =============
 
-    CONFIG.save(deps.storage, &config)?;
 
-    let initial_balance = UserBalance {
-        address: info.sender.clone(),
-        balance,
-        total_earned: Uint128::zero(),
-        quests_created: 0,
-        quests_completed: 0,
-    };
+const CONFIG: Item<Config> = Item::new("config");
+const PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
+const TOKEN_INFO: Item<TokenInfo> = Item::new("token_info");
+const TREASURY: Item<Uint128> = Item::new("treasury");
+const ALLOWANCES: Map<(&Addr, &Addr), Allowance> = Map::new("allowances");
+const ESCROW: Map<u64, Uint128> = Map::new("escrow");
+const SUBMISSIONS: Map<(u64, &Addr), Submission> = Map::new("submissions");
+const CATEGORIES: Map<&str, Category> = Map::new("categories");
+const REPUTATION: Map<&Addr, Reputation> = Map::new("reputation");
+const REFERRERS: Map<&Addr, Addr> = Map::new("referrers");
+const REFERRAL_STATS: Map<&Addr, ReferralStats> = Map::new("referral_stats");
+const PENDING_BADGE: Item<(u64, Addr)> = Item::new("pending_badge");
+const QUEST_TAGS: Map<(&str, u64), Empty> = Map::new("quest_tags");
 
-    BALANCES.save(deps.storage, &info.sender, &initial_balance)?;
 
-    Ok(Response::new()
-        .add_attribute("method", "instantiate")
-        .add_attribute("owner", owner)
-        .add_attribute("initial_balance", msg.initial_balance))
+pub struct QuestIndexes<'a> {
+    pub status: MultiIndex<'a, String, Quest, u64>,
+    pub creator: MultiIndex<'a, Addr, Quest, u64>,
+    pub open_deadline: MultiIndex<'a, u64, Quest, u64>,
 }
 
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    msg: ExecuteMsg,
-) -> StdResult<Response> {
-    match msg {
-        ExecuteMsg::CreateQuest {
-            name,
-            description,
-            reward_amount,
-        } => execute_create_quest(deps, env, info, name, description, reward_amount),
-        ExecuteMsg::CompleteQuest { quest_id } => execute_complete_quest(deps, env, info, quest_id),
-        ExecuteMsg::Transfer { recipient, amount } => {
-            execute_transfer(deps, info, recipient, amount)
-        }
-        ExecuteMsg::AdminWithdraw { amount } => execute_admin_withdraw(deps, info, amount),
+impl<'a> IndexList<Quest> for QuestIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Quest>> + '_> {
+        let v: Vec<&dyn Index<Quest>> = vec![&self.status, &self.creator, &self.open_deadline];
+        Box::new(v.into_iter())
     }
 }
 
 
-// ============= EXECUTE HANDLERS =============
-
+const QUESTS: IndexedMap<u64, Quest, QuestIndexes> = IndexedMap::new(
+    "quests",
+    QuestIndexes {
+        status: MultiIndex::new(
+            |_pk, quest| quest.status.as_str().to_string(),
+            "quests",
+            "quests__status",
+        ),
+        creator: MultiIndex::new(|_pk, quest| quest.creator.clone(), "quests", "quests__creator"),
+        // Only open quests with a deadline can expire; everything else is parked
+        // at u64::MAX so the expiry crank never has to step over it.
+        open_deadline: MultiIndex::new(
+            |_pk, quest| match (&quest.status, quest.deadline) {
+                (QuestStatus::Open, Some(deadline)) => deadline,
+                _ => u64::MAX,
+            },
+            "quests",
+            "quests__open_deadline",
+        ),
+    },
+);
 
-fn execute_create_quest(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    name: String,
-    description: String,
-    reward_amount: String,
-) -> StdResult<Response> {
-    let reward = Uint128::from_str(&reward_amount)
-        .map_err(|_| StdError::generic_err("reward_amount must be a valid number"))?;
 
-    let config = CONFIG.load(deps.storage)?;
+pub struct BalanceIndexes<'a> {
+    pub total_earned: MultiIndex<'a, u128, UserBalance, Addr>,
+    pub quests_completed: MultiIndex<'a, u64, UserBalance, Addr>,
+}
 
-    let mut user_balance = get_or_create_balance(deps.storage, &info.sender)?;
 
-    if user_balance.balance < config.quest_creation_fee {
-        return Err(StdError::generic_err(
-            format!(
-                "Недостаточно токенов. Требуется: {}, у вас: {}",
-                config.quest_creation_fee, user_balance.balance
-            )
-        ));
+impl<'a> IndexList<UserBalance> for BalanceIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<UserBalance>> + '_> {
+        let v: Vec<&dyn Index<UserBalance>> = vec![&self.total_earned, &self.quests_completed];
+        Box::new(v.into_iter())
     }
+}
 
-    user_balance.balance -= config.quest_creation_fee;
-    user_balance.quests_created += 1;
 
-    let mut new_config = config.clone();
-    new_config.total_quests += 1;
-    let quest_id = new_config.total_quests;
+const BALANCES: IndexedMap<&Addr, UserBalance, BalanceIndexes> = IndexedMap::new(
+    "balances",
+    BalanceIndexes {
+        total_earned: MultiIndex::new(
+            |_pk, balance| balance.total_earned.u128(),
+            "balances",
+            "balances__total_earned",
+        ),
+        quests_completed: MultiIndex::new(
+            |_pk, balance| balance.quests_completed,
+            "balances",
+            "balances__quests_completed",
+        ),
+    },
+);
 
-    let quest = Quest {
-        id: quest_id,
-        creator: info.sender.clone(),
-        name: name.clone(),
-        description,
-        reward_amount: reward,
-        completed: false,
-        completed_by: None,
-        created_at: env.block.time.seconds(),
-        completed_at: None,
-    };
 
-    CONFIG.save(deps.storage, &new_config)?;
-    QUESTS.save(deps.storage, quest_id, &quest)?;
-    BALANCES.save(deps.storage, &info.sender, &user_balance)?;
+pub struct DisputeIndexes<'a> {
+    pub status: MultiIndex<'a, String, Dispute, (u64, Addr)>,
+}
 
-    let mut user_quests = USER_QUESTS
-        .may_load(deps.storage, &info.sender)?
-        .unwrap_or_default();
-    user_quests.push(quest_id);
-    USER_QUESTS.save(deps.storage, &info.sender, &user_quests)?;
 
-    Ok(Response::new()
-        .add_attribute("method", "create_quest")
-        .add_attribute("quest_id", quest_id.to_string())
-        .add_attribute("quest_name", name)
-        .add_attribute("creator", info.sender.to_string())
-        .add_attribute("fee_deducted", config.quest_creation_fee.to_string())
-        .add_attribute("reward", reward.to_string())
-        .add_attribute("new_balance", user_balance.balance.to_string()))
+impl<'a> IndexList<Dispute> for DisputeIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Dispute>> + '_> {
+        let v: Vec<&dyn Index<Dispute>> = vec![&self.status];
+        Box::new(v.into_iter())
+    }
+}
+
+
+const DISPUTES: IndexedMap<(u64, &Addr), Dispute, DisputeIndexes> = IndexedMap::new(
+    "disputes",
+    DisputeIndexes {
+        status: MultiIndex::new(
+            |_pk, dispute| dispute.status.as_str().to_string(),
+            "disputes",
+            "disputes__status",
+        ),
+    },
+);
+
+
+pub struct CompletionIndexes<'a> {
+    pub completed_by: MultiIndex<'a, Addr, Completion, (u64, Addr)>,
+}
+
+
+impl<'a> IndexList<Completion> for CompletionIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Completion>> + '_> {
+        let v: Vec<&dyn Index<Completion>> = vec![&self.completed_by];
+        Box::new(v.into_iter())
+    }
+}
+
+
+const COMPLETIONS: IndexedMap<(u64, &Addr), Completion, CompletionIndexes> = IndexedMap::new(
+    "completions",
+    CompletionIndexes {
+        completed_by: MultiIndex::new(
+            |_pk, completion| completion.completer.clone(),
+            "completions",
+            "completions__completed_by",
+        ),
+    },
+);
+
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+const MAX_QUEST_SCAN: usize = 100;
+const DEFAULT_REVIEW_PERIOD: u64 = 7 * 24 * 60 * 60;
+const MAX_REVIEW_PERIOD: u64 = 365 * 24 * 60 * 60;
+const DEFAULT_TOKEN_NAME: &str = "Quest Token";
+const DEFAULT_TOKEN_SYMBOL: &str = "QUEST";
+const DEFAULT_TOKEN_DECIMALS: u8 = 6;
+const DEFAULT_MAX_NAME_LENGTH: u32 = 64;
+const DEFAULT_MAX_DESCRIPTION_LENGTH: u32 = 1024;
+const MAX_BPS: u16 = 10_000;
+const MAX_QUEST_TAGS: u32 = 5;
+const MAX_TAG_LENGTH: usize = 32;
+const REPUTATION_APPROVED: i64 = 10;
+const REPUTATION_REJECTED: i64 = -5;
+const REPUTATION_DISPUTE_LOST: i64 = -10;
+const REPUTATION_PROMPT_PAYOUT: i64 = 5;
+const BADGE_MINT_REPLY_ID: u64 = 1;
+
+
+// ============= MESSAGES =============
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct InstantiateMsg {
+    pub owner: Option<String>,
+    pub arbiter: Option<String>,
+    pub treasury: Option<String>,
+    pub badge_nft_contract: Option<String>,
+    pub denom: String,
+    /// Shorthand for a flat `fee_model`; setting both is rejected.
+    pub quest_creation_fee: Option<String>,
+    pub fee_model: Option<FeeModel>,
+    pub initial_balance: String,
+    pub review_period: Option<u64>,
+    pub max_name_length: Option<u32>,
+    pub max_description_length: Option<u32>,
+    pub referral_bonus_bps: Option<u16>,
+    pub token_name: Option<String>,
+    pub token_symbol: Option<String>,
+    pub token_decimals: Option<u8>,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct MigrateMsg {
+    pub denom: Option<String>,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct CreateQuestMsg {
+    pub name: String,
+    pub description: String,
+    pub reward_amount: String,
+    pub max_completions: Option<u64>,
+    pub deadline: Option<u64>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub min_reputation: Option<i64>,
+    pub badge: Option<BadgeTemplate>,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct UpdateConfigMsg {
+    pub arbiter: Option<String>,
+    pub review_period: Option<u64>,
+    pub fee_model: Option<FeeModel>,
+    pub treasury: Option<String>,
+    pub badge_nft_contract: Option<String>,
+    /// Stops minting badges; cannot be combined with `badge_nft_contract`.
+    pub clear_badge_nft_contract: Option<bool>,
+    pub max_name_length: Option<u32>,
+    pub max_description_length: Option<u32>,
+    pub referral_bonus_bps: Option<u16>,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    CreateQuest(CreateQuestMsg),
+    Receive(Cw20ReceiveMsg),
+    SubmitCompletion {
+        quest_id: u64,
+        proof: String,
+    },
+    ApproveSubmission {
+        quest_id: u64,
+        submitter: String,
+    },
+    RejectSubmission {
+        quest_id: u64,
+        submitter: String,
+        reason: String,
+    },
+    FinalizeSubmission {
+        quest_id: u64,
+        submitter: String,
+    },
+    Dispute {
+        quest_id: u64,
+        reason: String,
+    },
+    ResolveDispute {
+        quest_id: u64,
+        submitter: String,
+        winner: DisputeParty,
+    },
+    CancelQuest {
+        quest_id: u64,
+    },
+    ExpireQuests {
+        limit: Option<u32>,
+    },
+    Deposit {},
+    Withdraw {
+        amount: String,
+    },
+    Transfer {
+        recipient: String,
+        amount: String,
+    },
+    BatchTransfer {
+        transfers: Vec<(String, String)>,
+    },
+    Send {
+        contract: String,
+        amount: String,
+        msg: Binary,
+    },
+    Burn {
+        amount: String,
+    },
+    IncreaseAllowance {
+        spender: String,
+        amount: String,
+        expires: Option<Expiration>,
+    },
+    DecreaseAllowance {
+        spender: String,
+        amount: String,
+        expires: Option<Expiration>,
+    },
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: String,
+    },
+    AdminWithdraw {
+        amount: String,
+        recipient: Option<String>,
+    },
+    AddCategory {
+        tag: String,
+    },
+    RetireCategory {
+        tag: String,
+    },
+    RegisterReferrer {
+        referrer: String,
+    },
+    UpdateConfig(UpdateConfigMsg),
+    ProposeNewOwner {
+        owner: String,
+        expiry: Option<Expiration>,
+    },
+    AcceptOwnership {},
+    RenounceOwnership {},
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardMetric {
+    TotalEarned,
+    QuestsCompleted,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    GetConfig {},
+    GetBalance { address: String },
+    GetQuest { quest_id: u64 },
+    GetActiveQuests {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetUserQuests {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetCompletedQuests {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    ListQuests {
+        status: Option<QuestStatus>,
+        creator: Option<String>,
+        min_reward: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetUserStats { address: String },
+    GetPendingSubmissions { quest_id: u64 },
+    GetDisputes {
+        status: Option<DisputeStatus>,
+        start_after: Option<(u64, String)>,
+        limit: Option<u32>,
+    },
+    QuestsByTag {
+        tag: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetCategories {},
+    GetReputation { address: String },
+    GetReferrals { address: String },
+    Leaderboard {
+        metric: LeaderboardMetric,
+        /// `(score, address)` of the last entry seen, taken from `next_key`.
+        start_after: Option<(Uint128, String)>,
+        limit: Option<u32>,
+    },
+    SimulateCreateQuest {
+        reward_amount: String,
+        max_completions: Option<u64>,
+        reward_asset: Option<Asset>,
+    },
+    Balance { address: String },
+    TokenInfo {},
+    Allowance { owner: String, spender: String },
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+
+// ============= CW20 INTERFACE =============
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Cw20ReceiveMsg {
+    pub sender: String,
+    pub amount: Uint128,
+    pub msg: Binary,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum ReceiverExecuteMsg {
+    Receive(Cw20ReceiveMsg),
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Cw20ExecuteMsg {
+    Transfer { recipient: String, amount: Uint128 },
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    CreateQuest(CreateQuestMsg),
+}
+
+
+impl Cw20ReceiveMsg {
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = to_json_binary(&ReceiverExecuteMsg::Receive(self))?;
+        Ok(WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        }
+        .into())
+    }
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BalanceResponse {
+    pub balance: Uint128,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TokenInfoResponse {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub total_supply: Uint128,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AllowanceResponse {
+    pub allowance: Uint128,
+    pub expires: Expiration,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AllAccountsResponse {
+    pub accounts: Vec<String>,
+}
+
+
+// ============= CW721 INTERFACE =============
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BadgeTrait {
+    pub trait_type: String,
+    pub value: String,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BadgeMetadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub attributes: Option<Vec<BadgeTrait>>,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721ExecuteMsg {
+    Mint {
+        token_id: String,
+        owner: String,
+        token_uri: Option<String>,
+        extension: BadgeMetadata,
+    },
+}
+
+
+// ============= RESPONSE TYPES =============
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GetConfigResponse {
+    pub owner: Option<Addr>,
+    pub pending_owner: Option<PendingOwner>,
+    pub arbiter: Addr,
+    pub treasury: Addr,
+    pub badge_nft_contract: Option<Addr>,
+    pub denom: String,
+    pub fee_model: FeeModel,
+    pub review_period: u64,
+    pub max_name_length: u32,
+    pub max_description_length: u32,
+    pub referral_bonus_bps: u16,
+    pub total_quests: u64,
+    pub total_completed: u64,
+    pub total_fees_collected: Uint128,
+    pub treasury_balance: Uint128,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LeaderboardEntry {
+    pub address: Addr,
+    pub total_earned: Uint128,
+    pub quests_completed: u64,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LeaderboardResponse {
+    pub entries: Vec<LeaderboardEntry>,
+    pub next_key: Option<(Uint128, Addr)>,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReferralsResponse {
+    pub address: Addr,
+    pub referrer: Option<Addr>,
+    pub referrals: u64,
+    pub total_earned: Uint128,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CategoriesResponse {
+    pub categories: Vec<Category>,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SimulateCreateQuestResponse {
+    pub fee: Uint128,
+    pub escrow: Uint128,
+    pub total_cost: Uint128,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GetBalanceResponse {
+    pub address: Addr,
+    pub balance: Uint128,
+    pub total_earned: Uint128,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GetQuestResponse {
+    pub quest: Quest,
+    pub remaining_slots: u64,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GetActiveQuestsResponse {
+    pub quests: Vec<Quest>,
+    pub count: u64,
+    pub next_key: Option<u64>,
 }
 
 
-fn execute_complete_quest(
-    deps: DepsMut,
-    _env: Env,
-    info: MessageInfo,
-    quest_id: u64,
-) -> StdResult<Response> {
-    let mut quest = QUESTS.load(deps.storage, quest_id)
-        .map_err(|_| StdError::generic_err("Квест не найден"))?;
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct QuestsResponse {
+    pub quests: Vec<Quest>,
+    pub next_key: Option<u64>,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GetSubmissionsResponse {
+    pub submissions: Vec<Submission>,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GetDisputesResponse {
+    pub disputes: Vec<Dispute>,
+    pub next_key: Option<(u64, Addr)>,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GetUserStatsResponse {
+    pub address: Addr,
+    pub balance: Uint128,
+    pub total_earned: Uint128,
+    pub quests_created: u64,
+    pub quests_completed: u64,
+}
+
+
+// ============= HELPER FUNCTION =============
+
+
+fn get_or_create_balance(
+    storage: &dyn cosmwasm_std::Storage,
+    address: &Addr,
+) -> StdResult<UserBalance> {
+    Ok(BALANCES
+        .may_load(storage, address)?
+        .unwrap_or(UserBalance {
+            address: address.clone(),
+            balance: Uint128::zero(),
+            total_earned: Uint128::zero(),
+            quests_created: 0,
+            quests_completed: 0,
+        }))
+}
+
+
+fn parse_amount(value: &str, field: &str) -> Result<Uint128, ContractError> {
+    Uint128::from_str(value).map_err(|_| ContractError::InvalidAmount {
+        field: field.to_string(),
+    })
+}
+
+
+fn ensure_valid_limit(limit: u32, field: &str) -> Result<(), ContractError> {
+    if limit == 0 {
+        return Err(ContractError::InvalidInput {
+            reason: format!("{} must be at least 1", field),
+        });
+    }
+    Ok(())
+}
+
+
+fn ensure_valid_bps(bps: u16, field: &str) -> Result<(), ContractError> {
+    if bps > MAX_BPS {
+        return Err(ContractError::InvalidInput {
+            reason: format!("{} must not exceed {}", field, MAX_BPS),
+        });
+    }
+    Ok(())
+}
+
+
+fn ensure_valid_review_period(period: u64) -> Result<(), ContractError> {
+    if period > MAX_REVIEW_PERIOD {
+        return Err(ContractError::InvalidInput {
+            reason: format!("review_period must not exceed {} seconds", MAX_REVIEW_PERIOD),
+        });
+    }
+    Ok(())
+}
+
+
+fn ensure_max_length(value: &str, max: u32, field: &str) -> Result<(), ContractError> {
+    if value.chars().count() > max as usize {
+        return Err(ContractError::ContentTooLong {
+            field: field.to_string(),
+            max,
+        });
+    }
+    Ok(())
+}
+
+
+fn quest_escrow(reward: Uint128, max_completions: Option<u64>) -> Result<(u64, Uint128), ContractError> {
+    let max_completions = max_completions.unwrap_or(1);
+    if max_completions == 0 {
+        return Err(ContractError::InvalidInput {
+            reason: "max_completions must be at least 1".to_string(),
+        });
+    }
+
+    let escrow = reward
+        .checked_mul(Uint128::from(max_completions))
+        .map_err(StdError::overflow)?;
+
+    Ok((max_completions, escrow))
+}
+
+
+fn ensure_valid_tags(storage: &dyn cosmwasm_std::Storage, tags: &[String]) -> Result<(), ContractError> {
+    if tags.len() > MAX_QUEST_TAGS as usize {
+        return Err(ContractError::TooManyTags { max: MAX_QUEST_TAGS });
+    }
+
+    for (i, tag) in tags.iter().enumerate() {
+        if tags[..i].contains(tag) {
+            return Err(ContractError::InvalidInput {
+                reason: format!("duplicate tag {}", tag),
+            });
+        }
+
+        let category = CATEGORIES
+            .may_load(storage, tag)?
+            .ok_or_else(|| ContractError::CategoryNotFound { tag: tag.clone() })?;
+        if !category.active {
+            return Err(ContractError::CategoryRetired { tag: tag.clone() });
+        }
+    }
+
+    Ok(())
+}
+
+
+fn load_quest(storage: &dyn cosmwasm_std::Storage, quest_id: u64) -> Result<Quest, ContractError> {
+    QUESTS
+        .may_load(storage, quest_id)?
+        .ok_or(ContractError::QuestNotFound { quest_id })
+}
+
+
+fn move_balance(
+    storage: &mut dyn cosmwasm_std::Storage,
+    from: &Addr,
+    to: &Addr,
+    amount: Uint128,
+) -> Result<(UserBalance, UserBalance), ContractError> {
+    let mut sender_balance = get_or_create_balance(storage, from)?;
+
+    if sender_balance.balance < amount {
+        return Err(ContractError::InsufficientBalance {
+            required: amount,
+            available: sender_balance.balance,
+        });
+    }
+
+    sender_balance.balance -= amount;
+    BALANCES.save(storage, from, &sender_balance)?;
+
+    let mut recipient_balance = get_or_create_balance(storage, to)?;
+    recipient_balance.balance += amount;
+    BALANCES.save(storage, to, &recipient_balance)?;
+
+    let sender_balance = get_or_create_balance(storage, from)?;
+    Ok((sender_balance, recipient_balance))
+}
+
+
+fn spend_allowance(
+    storage: &mut dyn cosmwasm_std::Storage,
+    env: &Env,
+    owner: &Addr,
+    spender: &Addr,
+    amount: Uint128,
+) -> Result<Allowance, ContractError> {
+    let mut allowance = ALLOWANCES
+        .may_load(storage, (owner, spender))?
+        .unwrap_or_default();
+
+    if allowance.expires.is_expired(&env.block) {
+        return Err(ContractError::AllowanceExpired {});
+    }
+
+    if allowance.allowance < amount {
+        return Err(ContractError::InsufficientAllowance {
+            required: amount,
+            available: allowance.allowance,
+        });
+    }
+
+    allowance.allowance -= amount;
+    ALLOWANCES.save(storage, (owner, spender), &allowance)?;
+
+    Ok(allowance)
+}
+
+
+fn update_supply(
+    storage: &mut dyn cosmwasm_std::Storage,
+    minted: Uint128,
+    burned: Uint128,
+) -> StdResult<()> {
+    TOKEN_INFO.update(storage, |mut info| -> StdResult<_> {
+        info.total_supply = info.total_supply + minted - burned;
+        Ok(info)
+    })?;
+    Ok(())
+}
+
+
+fn is_owner(config: &Config, sender: &Addr) -> bool {
+    config.owner.as_ref() == Some(sender)
+}
+
+
+fn ensure_owner(config: &Config, sender: &Addr) -> Result<Addr, ContractError> {
+    match &config.owner {
+        Some(owner) if owner == sender => Ok(owner.clone()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+
+fn is_active(quest: &Quest) -> bool {
+    matches!(quest.status, QuestStatus::Open | QuestStatus::Submitted)
+}
+
+
+fn has_pending_submissions(storage: &dyn cosmwasm_std::Storage, quest_id: u64) -> bool {
+    SUBMISSIONS
+        .prefix(quest_id)
+        .range(storage, None, None, Order::Ascending)
+        .filter_map(|r| r.ok())
+        .any(|(_, submission)| submission.status == SubmissionStatus::Pending)
+}
+
+
+/// Closes submissions still awaiting review once the last slot is filled.
+/// They were never judged, so reputation is left untouched.
+fn close_pending_submissions(
+    storage: &mut dyn cosmwasm_std::Storage,
+    quest_id: u64,
+    now: u64,
+) -> StdResult<()> {
+    let pending: Vec<Submission> = SUBMISSIONS
+        .prefix(quest_id)
+        .range(storage, None, None, Order::Ascending)
+        .map(|r| r.map(|(_, submission)| submission))
+        .filter(|r| r.as_ref().map_or(true, |s| s.status == SubmissionStatus::Pending))
+        .collect::<StdResult<_>>()?;
+
+    for mut submission in pending {
+        submission.status = SubmissionStatus::Closed;
+        submission.reviewed_at = Some(now);
+        SUBMISSIONS.save(storage, (quest_id, &submission.submitter), &submission)?;
+    }
+    Ok(())
+}
+
+
+/// A rejected submitter has `review_period` after the rejection to open a
+/// dispute. Returns the latest such window still running at `now`, so the
+/// escrow stays put until every undisputed rejection has had its chance.
+fn open_dispute_window(
+    storage: &dyn cosmwasm_std::Storage,
+    quest_id: u64,
+    review_period: u64,
+    now: u64,
+) -> StdResult<Option<u64>> {
+    let mut ends_at = None;
+    for item in SUBMISSIONS.prefix(quest_id).range(storage, None, None, Order::Ascending) {
+        let (submitter, submission) = item?;
+        if submission.status != SubmissionStatus::Rejected
+            || DISPUTES.has(storage, (quest_id, &submitter))
+        {
+            continue;
+        }
+        let window_end = submission
+            .reviewed_at
+            .unwrap_or(submission.submitted_at)
+            .saturating_add(review_period);
+        if window_end > now {
+            ends_at = ends_at.max(Some(window_end));
+        }
+    }
+    Ok(ends_at)
+}
+
+
+/// Reviews and disputes are frozen while a dispute is open, so every other
+/// submitter gets the frozen time back on their review or dispute clock.
+fn extend_review_clocks(
+    storage: &mut dyn cosmwasm_std::Storage,
+    quest_id: u64,
+    frozen_for: u64,
+) -> StdResult<()> {
+    let submissions: Vec<Submission> = SUBMISSIONS
+        .prefix(quest_id)
+        .range(storage, None, None, Order::Ascending)
+        .map(|r| r.map(|(_, submission)| submission))
+        .collect::<StdResult<_>>()?;
+
+    for mut submission in submissions {
+        match submission.status {
+            SubmissionStatus::Pending => {
+                submission.submitted_at = submission.submitted_at.saturating_add(frozen_for);
+            }
+            SubmissionStatus::Rejected if !DISPUTES.has(storage, (quest_id, &submission.submitter)) => {
+                submission.reviewed_at = submission.reviewed_at.map(|at| at.saturating_add(frozen_for));
+            }
+            _ => continue,
+        }
+        SUBMISSIONS.save(storage, (quest_id, &submission.submitter), &submission)?;
+    }
+    Ok(())
+}
+
+
+fn ensure_not_disputed(quest: &Quest) -> Result<(), ContractError> {
+    if quest.status == QuestStatus::Disputed {
+        return Err(ContractError::QuestDisputed {});
+    }
+    Ok(())
+}
+
+
+fn load_pending_submission(
+    storage: &dyn cosmwasm_std::Storage,
+    quest_id: u64,
+    submitter: &Addr,
+) -> Result<Submission, ContractError> {
+    let submission = SUBMISSIONS
+        .may_load(storage, (quest_id, submitter))?
+        .ok_or(ContractError::SubmissionNotFound {})?;
+
+    if submission.status != SubmissionStatus::Pending {
+        return Err(ContractError::SubmissionAlreadyReviewed {});
+    }
+
+    Ok(submission)
+}
+
+
+fn asset_label(asset: &Asset) -> String {
+    match asset {
+        Asset::Internal => "internal".to_string(),
+        Asset::Native { denom } => denom.clone(),
+        Asset::Cw20 { contract } => contract.to_string(),
+    }
+}
+
+
+fn transfer_asset(
+    storage: &mut dyn cosmwasm_std::Storage,
+    asset: &Asset,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<Option<CosmosMsg>> {
+    if amount.is_zero() {
+        return Ok(None);
+    }
+
+    match asset {
+        Asset::Internal => {
+            let mut balance = get_or_create_balance(storage, recipient)?;
+            balance.balance += amount;
+            BALANCES.save(storage, recipient, &balance)?;
+            Ok(None)
+        }
+        Asset::Native { denom } => Ok(Some(
+            BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: coins(amount.u128(), denom),
+            }
+            .into(),
+        )),
+        Asset::Cw20 { contract } => Ok(Some(
+            WasmMsg::Execute {
+                contract_addr: contract.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount,
+                })?,
+                funds: vec![],
+            }
+            .into(),
+        )),
+    }
+}
+
+
+fn complete_quest(
+    storage: &mut dyn cosmwasm_std::Storage,
+    env: &Env,
+    quest: &mut Quest,
+    completer: &Addr,
+) -> Result<(UserBalance, Option<CosmosMsg>), ContractError> {
+    if quest.status == QuestStatus::Completed {
+        return Err(ContractError::QuestAlreadyCompleted {});
+    }
+
+    if COMPLETIONS.may_load(storage, (quest.id, completer))?.is_some() {
+        return Err(ContractError::AlreadyCompleted {});
+    }
+
+    let escrowed = ESCROW
+        .may_load(storage, quest.id)?
+        .unwrap_or_default();
+
+    if escrowed < quest.reward_amount {
+        return Err(ContractError::InsufficientEscrow {});
+    }
+
+    let payout = transfer_asset(storage, &quest.reward_asset, completer, quest.reward_amount)?;
+
+    let mut completer_balance = get_or_create_balance(storage, completer)?;
+    if quest.reward_asset == Asset::Internal {
+        completer_balance.total_earned += quest.reward_amount;
+    }
+    completer_balance.quests_completed += 1;
+
+    let now = env.block.time.seconds();
+    quest.completions += 1;
+    quest.completed_by = Some(completer.clone());
+    quest.completed_at = Some(now);
+
+    if quest.completions >= quest.max_completions {
+        quest.status = QuestStatus::Completed;
+
+        let mut config = CONFIG.load(storage)?;
+        config.total_completed += 1;
+        CONFIG.save(storage, &config)?;
+
+        close_pending_submissions(storage, quest.id, now)?;
+    } else if has_pending_submissions(storage, quest.id) {
+        quest.status = QuestStatus::Submitted;
+    } else {
+        quest.status = QuestStatus::Open;
+    }
+
+    let remaining_escrow = escrowed - quest.reward_amount;
+    if remaining_escrow.is_zero() {
+        ESCROW.remove(storage, quest.id);
+    } else {
+        ESCROW.save(storage, quest.id, &remaining_escrow)?;
+    }
+
+    QUESTS.save(storage, quest.id, quest)?;
+    COMPLETIONS.save(
+        storage,
+        (quest.id, completer),
+        &Completion {
+            quest_id: quest.id,
+            completer: completer.clone(),
+            completed_at: now,
+        },
+    )?;
+    BALANCES.save(storage, completer, &completer_balance)?;
+
+    Ok((completer_balance, payout))
+}
+
+
+fn update_reputation(
+    storage: &mut dyn cosmwasm_std::Storage,
+    address: &Addr,
+    action: impl FnOnce(&mut Reputation),
+) -> StdResult<Reputation> {
+    let mut reputation = REPUTATION.may_load(storage, address)?.unwrap_or_default();
+    action(&mut reputation);
+    REPUTATION.save(storage, address, &reputation)?;
+    Ok(reputation)
+}
+
+
+fn approve_submission(
+    storage: &mut dyn cosmwasm_std::Storage,
+    env: &Env,
+    quest: &mut Quest,
+    submission: &mut Submission,
+) -> Result<(UserBalance, Option<CosmosMsg>), ContractError> {
+    submission.status = SubmissionStatus::Approved;
+    submission.reviewed_at = Some(env.block.time.seconds());
+    SUBMISSIONS.save(storage, (quest.id, &submission.submitter), submission)?;
+
+    update_reputation(storage, &submission.submitter, |reputation| {
+        reputation.score += REPUTATION_APPROVED;
+        reputation.approved += 1;
+    })?;
+
+    complete_quest(storage, env, quest, &submission.submitter)
+}
+
+
+fn mint_badge(
+    storage: &mut dyn cosmwasm_std::Storage,
+    quest: &Quest,
+    completer: &Addr,
+) -> StdResult<Option<SubMsg>> {
+    let Some(template) = &quest.badge else {
+        return Ok(None);
+    };
+    let Some(contract) = CONFIG.load(storage)?.badge_nft_contract else {
+        return Ok(None);
+    };
+
+    let mint = Cw721ExecuteMsg::Mint {
+        token_id: format!("{}-{}", quest.id, completer),
+        owner: completer.to_string(),
+        token_uri: template.token_uri.clone(),
+        extension: BadgeMetadata {
+            name: Some(template.name.clone()),
+            description: template.description.clone(),
+            image: template.image.clone(),
+            attributes: Some(vec![BadgeTrait {
+                trait_type: "quest_id".to_string(),
+                value: quest.id.to_string(),
+            }]),
+        },
+    };
+
+    PENDING_BADGE.save(storage, &(quest.id, completer.clone()))?;
+
+    Ok(Some(SubMsg::reply_always(
+        WasmMsg::Execute {
+            contract_addr: contract.to_string(),
+            msg: to_json_binary(&mint)?,
+            funds: vec![],
+        },
+        BADGE_MINT_REPLY_ID,
+    )))
+}
+
+
+/// Pays the completer's referrer `referral_bonus_bps` of the reward out of the
+/// treasury. The completer's own reward is untouched; the bonus is capped at
+/// whatever the treasury currently holds.
+fn pay_referral_bonus(
+    storage: &mut dyn cosmwasm_std::Storage,
+    quest: &Quest,
+    completer: &Addr,
+) -> StdResult<Option<Event>> {
+    if quest.reward_asset != Asset::Internal {
+        return Ok(None);
+    }
+    let Some(referrer) = REFERRERS.may_load(storage, completer)? else {
+        return Ok(None);
+    };
+
+    let config = CONFIG.load(storage)?;
+    let treasury = TREASURY.load(storage)?;
+    let bonus = quest
+        .reward_amount
+        .multiply_ratio(config.referral_bonus_bps, MAX_BPS)
+        .min(treasury);
+    if bonus.is_zero() {
+        return Ok(None);
+    }
+
+    TREASURY.save(storage, &(treasury - bonus))?;
+
+    let mut referrer_balance = get_or_create_balance(storage, &referrer)?;
+    referrer_balance.balance += bonus;
+    BALANCES.save(storage, &referrer, &referrer_balance)?;
+
+    let mut stats = REFERRAL_STATS.may_load(storage, &referrer)?.unwrap_or_default();
+    stats.total_earned += bonus;
+    REFERRAL_STATS.save(storage, &referrer, &stats)?;
+
+    Ok(Some(
+        Event::new("referral_bonus")
+            .add_attribute("referrer", referrer.to_string())
+            .add_attribute("referee", completer.to_string())
+            .add_attribute("quest_id", quest.id.to_string())
+            .add_attribute("bonus", bonus.to_string()),
+    ))
+}
+
+
+fn is_overdue(quest: &Quest, now: u64) -> bool {
+    matches!(quest.deadline, Some(deadline) if now > deadline)
+}
+
+
+fn refund_escrow(
+    storage: &mut dyn cosmwasm_std::Storage,
+    quest: &Quest,
+) -> StdResult<(Uint128, Option<CosmosMsg>)> {
+    let refund = ESCROW
+        .may_load(storage, quest.id)?
+        .unwrap_or_default();
+
+    ESCROW.remove(storage, quest.id);
+    let refund_msg = transfer_asset(storage, &quest.reward_asset, &quest.creator, refund)?;
+
+    Ok((refund, refund_msg))
+}
+
+
+// ============= ENTRY POINTS =============
+
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let owner = if let Some(owner_addr) = msg.owner {
+        deps.api.addr_validate(&owner_addr)?
+    } else {
+        info.sender.clone()
+    };
+
+    let arbiter = if let Some(arbiter_addr) = msg.arbiter {
+        deps.api.addr_validate(&arbiter_addr)?
+    } else {
+        owner.clone()
+    };
+
+    let treasury = if let Some(treasury_addr) = msg.treasury {
+        deps.api.addr_validate(&treasury_addr)?
+    } else {
+        owner.clone()
+    };
+
+    let badge_nft_contract = msg
+        .badge_nft_contract
+        .map(|contract| deps.api.addr_validate(&contract))
+        .transpose()?;
+
+    let max_name_length = msg.max_name_length.unwrap_or(DEFAULT_MAX_NAME_LENGTH);
+    let max_description_length = msg
+        .max_description_length
+        .unwrap_or(DEFAULT_MAX_DESCRIPTION_LENGTH);
+    ensure_valid_limit(max_name_length, "max_name_length")?;
+    ensure_valid_limit(max_description_length, "max_description_length")?;
+
+    let fee_model = match (msg.fee_model, msg.quest_creation_fee) {
+        (Some(_), Some(_)) => {
+            return Err(ContractError::InvalidInput {
+                reason: "set either quest_creation_fee or fee_model, not both".to_string(),
+            })
+        }
+        (Some(fee_model), None) => fee_model,
+        (None, Some(fee)) => FeeModel::Flat {
+            amount: parse_amount(&fee, "quest_creation_fee")?,
+        },
+        (None, None) => FeeModel::Flat { amount: Uint128::zero() },
+    };
+    fee_model.validate()?;
+
+    let referral_bonus_bps = msg.referral_bonus_bps.unwrap_or(0);
+    ensure_valid_bps(referral_bonus_bps, "referral_bonus_bps")?;
+
+    let review_period = msg.review_period.unwrap_or(DEFAULT_REVIEW_PERIOD);
+    ensure_valid_review_period(review_period)?;
+    
+    // The seeded balance is an ordinary deposit: it must arrive as coins so
+    // that every ledger token can later be withdrawn.
+    let balance = parse_amount(&msg.initial_balance, "initial_balance")?;
+    let paid = if balance.is_zero() {
+        nonpayable(&info)?;
+        balance
+    } else {
+        must_pay(&info, &msg.denom)?
+    };
+    if paid != balance {
+        return Err(ContractError::FundsMismatch { expected: balance });
+    }
+
+    let config = Config {
+        owner: Some(owner.clone()),
+        arbiter: arbiter.clone(),
+        treasury: treasury.clone(),
+        badge_nft_contract,
+        denom: msg.denom.clone(),
+        fee_model,
+        review_period,
+        max_name_length,
+        max_description_length,
+        referral_bonus_bps,
+        total_quests: 0,
+        total_completed: 0,
+        total_fees_collected: Uint128::zero(),
+    };
+
+    CONFIG.save(deps.storage, &config)?;
+    TREASURY.save(deps.storage, &Uint128::zero())?;
+
+    let initial_balance = UserBalance {
+        address: info.sender.clone(),
+        balance,
+        total_earned: Uint128::zero(),
+        quests_created: 0,
+        quests_completed: 0,
+    };
+
+    BALANCES.save(deps.storage, &info.sender, &initial_balance)?;
+
+    let token_info = TokenInfo {
+        name: msg.token_name.unwrap_or_else(|| DEFAULT_TOKEN_NAME.to_string()),
+        symbol: msg.token_symbol.unwrap_or_else(|| DEFAULT_TOKEN_SYMBOL.to_string()),
+        decimals: msg.token_decimals.unwrap_or(DEFAULT_TOKEN_DECIMALS),
+        total_supply: balance,
+    };
+    TOKEN_INFO.save(deps.storage, &token_info)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", owner)
+        .add_attribute("arbiter", arbiter)
+        .add_attribute("treasury", treasury)
+        .add_attribute("denom", msg.denom)
+        .add_attribute("initial_balance", msg.initial_balance))
+}
+
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // Coins sent with any other message would sit in the contract without a
+    // ledger entry backing them, so only the paying handlers accept funds.
+    if !matches!(msg, ExecuteMsg::CreateQuest(_) | ExecuteMsg::Deposit {}) {
+        nonpayable(&info)?;
+    }
+
+    match msg {
+        ExecuteMsg::CreateQuest(msg) => {
+            let funding = if info.funds.is_empty() {
+                None
+            } else {
+                let coin = one_coin(&info)?;
+                Some((Asset::Native { denom: coin.denom }, coin.amount))
+            };
+            execute_create_quest(deps, env, info.sender, msg, funding)
+        }
+        ExecuteMsg::Receive(wrapper) => execute_receive(deps, env, info, wrapper),
+        ExecuteMsg::SubmitCompletion { quest_id, proof } => {
+            execute_submit_completion(deps, env, info, quest_id, proof)
+        }
+        ExecuteMsg::ApproveSubmission { quest_id, submitter } => {
+            execute_approve_submission(deps, env, info, quest_id, submitter)
+        }
+        ExecuteMsg::RejectSubmission { quest_id, submitter, reason } => {
+            execute_reject_submission(deps, env, info, quest_id, submitter, reason)
+        }
+        ExecuteMsg::FinalizeSubmission { quest_id, submitter } => {
+            execute_finalize_submission(deps, env, quest_id, submitter)
+        }
+        ExecuteMsg::Dispute { quest_id, reason } => {
+            execute_dispute(deps, env, info, quest_id, reason)
+        }
+        ExecuteMsg::ResolveDispute { quest_id, submitter, winner } => {
+            execute_resolve_dispute(deps, env, info, quest_id, submitter, winner)
+        }
+        ExecuteMsg::CancelQuest { quest_id } => execute_cancel_quest(deps, env, info, quest_id),
+        ExecuteMsg::ExpireQuests { limit } => execute_expire_quests(deps, env, limit),
+        ExecuteMsg::Deposit {} => execute_deposit(deps, info),
+        ExecuteMsg::Withdraw { amount } => execute_withdraw(deps, info, amount),
+        ExecuteMsg::Transfer { recipient, amount } => {
+            execute_transfer(deps, info, recipient, amount)
+        }
+        ExecuteMsg::BatchTransfer { transfers } => execute_batch_transfer(deps, info, transfers),
+        ExecuteMsg::Send { contract, amount, msg } => {
+            execute_send(deps, info, contract, amount, msg)
+        }
+        ExecuteMsg::Burn { amount } => execute_burn(deps, info, amount),
+        ExecuteMsg::IncreaseAllowance { spender, amount, expires } => {
+            execute_increase_allowance(deps, env, info, spender, amount, expires)
+        }
+        ExecuteMsg::DecreaseAllowance { spender, amount, expires } => {
+            execute_decrease_allowance(deps, env, info, spender, amount, expires)
+        }
+        ExecuteMsg::TransferFrom { owner, recipient, amount } => {
+            execute_transfer_from(deps, env, info, owner, recipient, amount)
+        }
+        ExecuteMsg::AdminWithdraw { amount, recipient } => {
+            execute_admin_withdraw(deps, info, amount, recipient)
+        }
+        ExecuteMsg::AddCategory { tag } => execute_add_category(deps, env, info, tag),
+        ExecuteMsg::RetireCategory { tag } => execute_retire_category(deps, info, tag),
+        ExecuteMsg::RegisterReferrer { referrer } => {
+            execute_register_referrer(deps, info, referrer)
+        }
+        ExecuteMsg::UpdateConfig(msg) => execute_update_config(deps, info, msg),
+        ExecuteMsg::ProposeNewOwner { owner, expiry } => {
+            execute_propose_new_owner(deps, env, info, owner, expiry)
+        }
+        ExecuteMsg::AcceptOwnership {} => execute_accept_ownership(deps, env, info),
+        ExecuteMsg::RenounceOwnership {} => execute_renounce_ownership(deps, info),
+    }
+}
+
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let new_version = Version::parse(CONTRACT_VERSION)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    // Deployments from before cw2 tracking carry the 0.1.0 storage layout.
+    let stored_version = match get_contract_version(deps.storage) {
+        Ok(info) => {
+            if info.contract != CONTRACT_NAME {
+                return Err(ContractError::WrongContract {
+                    contract: info.contract,
+                });
+            }
+            Version::parse(&info.version).map_err(|e| StdError::generic_err(e.to_string()))?
+        }
+        Err(_) => Version::new(0, 1, 0),
+    };
+
+    if stored_version > new_version {
+        return Err(ContractError::CannotDowngrade {
+            stored: stored_version.to_string(),
+            current: new_version.to_string(),
+        });
+    }
+
+    let mut response = Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored_version.to_string())
+        .add_attribute("to_version", new_version.to_string());
+
+    if stored_version < Version::new(0, 2, 0) {
+        let migrated = migrate_v0_1_to_v0_2(deps.storage, &env, msg)?;
+        ensure_supply_backed(deps.as_ref(), &env)?;
+        response = response.add_attribute("quests_migrated", migrated.to_string());
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(response)
+}
+
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.id != BADGE_MINT_REPLY_ID {
+        return Err(ContractError::InvalidInput {
+            reason: format!("unknown reply id {}", msg.id),
+        });
+    }
+
+    let (quest_id, completer) = PENDING_BADGE.load(deps.storage)?;
+    PENDING_BADGE.remove(deps.storage);
+
+    // A failed mint is swallowed here so the reward payout still goes through.
+    let event = match msg.result {
+        SubMsgResult::Ok(_) => Event::new("badge_minted"),
+        SubMsgResult::Err(error) => Event::new("badge_mint_failed").add_attribute("error", error),
+    };
+
+    Ok(Response::new().add_event(
+        event
+            .add_attribute("quest_id", quest_id.to_string())
+            .add_attribute("completer", completer.to_string()),
+    ))
+}
+
+
+// ============= EXECUTE HANDLERS =============
+
+
+fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let creator = deps.api.addr_validate(&wrapper.sender)?;
+    let asset = Asset::Cw20 { contract: info.sender };
+
+    match from_json(&wrapper.msg)? {
+        ReceiveMsg::CreateQuest(msg) => {
+            execute_create_quest(deps, env, creator, msg, Some((asset, wrapper.amount)))
+        }
+    }
+}
+
+
+fn execute_create_quest(
+    deps: DepsMut,
+    env: Env,
+    creator: Addr,
+    msg: CreateQuestMsg,
+    funding: Option<(Asset, Uint128)>,
+) -> Result<Response, ContractError> {
+    let CreateQuestMsg {
+        name,
+        description,
+        reward_amount,
+        max_completions,
+        deadline,
+        tags,
+        min_reputation,
+        badge,
+    } = msg;
+
+    let reward = parse_amount(&reward_amount, "reward_amount")?;
+    let (max_completions, escrow) = quest_escrow(reward, max_completions)?;
+
+    if let Some(deadline) = deadline {
+        if deadline <= env.block.time.seconds() {
+            return Err(ContractError::DeadlineInPast {});
+        }
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+
+    ensure_max_length(&name, config.max_name_length, "name")?;
+    ensure_max_length(&description, config.max_description_length, "description")?;
+    ensure_valid_tags(deps.storage, &tags)?;
+
+    let mut user_balance = get_or_create_balance(deps.storage, &creator)?;
+
+    let (reward_asset, funded) = funding.unwrap_or((Asset::Internal, escrow));
+    if funded != escrow {
+        return Err(ContractError::FundsMismatch { expected: escrow });
+    }
+
+    let fee = config.fee_model.fee_for_asset(&reward_asset, escrow);
+    let required = match reward_asset {
+        Asset::Internal => fee + escrow,
+        Asset::Native { .. } | Asset::Cw20 { .. } => fee,
+    };
+
+    if user_balance.balance < required {
+        return Err(ContractError::InsufficientBalance {
+            required,
+            available: user_balance.balance,
+        });
+    }
+
+    user_balance.balance -= required;
+    user_balance.quests_created += 1;
+
+    let mut new_config = config.clone();
+    new_config.total_quests += 1;
+    new_config.total_fees_collected += fee;
+    let quest_id = new_config.total_quests;
+
+    TREASURY.update(deps.storage, |treasury| -> StdResult<_> { Ok(treasury + fee) })?;
+
+    let quest = Quest {
+        id: quest_id,
+        creator: creator.clone(),
+        name: name.clone(),
+        description,
+        reward_asset: reward_asset.clone(),
+        reward_amount: reward,
+        max_completions,
+        completions: 0,
+        status: QuestStatus::Open,
+        completed_by: None,
+        created_at: env.block.time.seconds(),
+        completed_at: None,
+        deadline,
+        tags,
+        min_reputation,
+        badge,
+    };
+
+    CONFIG.save(deps.storage, &new_config)?;
+    QUESTS.save(deps.storage, quest_id, &quest)?;
+    ESCROW.save(deps.storage, quest_id, &escrow)?;
+    BALANCES.save(deps.storage, &creator, &user_balance)?;
+    for tag in &quest.tags {
+        QUEST_TAGS.save(deps.storage, (tag, quest_id), &Empty {})?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "create_quest")
+        .add_attribute("quest_id", quest_id.to_string())
+        .add_attribute("quest_name", name)
+        .add_attribute("creator", creator.to_string())
+        .add_attribute("fee_deducted", fee.to_string())
+        .add_attribute("reward", reward.to_string())
+        .add_attribute("reward_asset", asset_label(&reward_asset))
+        .add_attribute("max_completions", max_completions.to_string())
+        .add_attribute("escrowed", escrow.to_string())
+        .add_attributes(quest.tags.iter().map(|tag| ("tag", tag.as_str())))
+        .add_attribute("new_balance", user_balance.balance.to_string()))
+}
+
+
+fn execute_submit_completion(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    quest_id: u64,
+    proof: String,
+) -> Result<Response, ContractError> {
+    let mut quest = load_quest(deps.storage, quest_id)?;
+
+    if quest.status == QuestStatus::Completed {
+        return Err(ContractError::QuestAlreadyCompleted {});
+    }
+
+    if !is_active(&quest) {
+        return Err(ContractError::QuestNotActive {});
+    }
+
+    if is_overdue(&quest, env.block.time.seconds()) {
+        return Err(ContractError::QuestExpired {});
+    }
+
+    if quest.creator == info.sender {
+        return Err(ContractError::CannotCompleteOwnQuest {});
+    }
+
+    if proof.trim().is_empty() {
+        return Err(ContractError::InvalidInput {
+            reason: "proof must not be empty".to_string(),
+        });
+    }
+
+    if let Some(required) = quest.min_reputation {
+        let actual = REPUTATION
+            .may_load(deps.storage, &info.sender)?
+            .unwrap_or_default()
+            .score;
+        if actual < required {
+            return Err(ContractError::InsufficientReputation { required, actual });
+        }
+    }
+
+    if let Some(existing) = SUBMISSIONS.may_load(deps.storage, (quest_id, &info.sender))? {
+        if existing.status != SubmissionStatus::Rejected {
+            return Err(ContractError::DuplicateSubmission {});
+        }
+    }
+
+    let submission = Submission {
+        quest_id,
+        submitter: info.sender.clone(),
+        proof: proof.clone(),
+        status: SubmissionStatus::Pending,
+        submitted_at: env.block.time.seconds(),
+        reviewed_at: None,
+        rejection_reason: None,
+    };
+
+    quest.status = QuestStatus::Submitted;
+
+    SUBMISSIONS.save(deps.storage, (quest_id, &info.sender), &submission)?;
+    QUESTS.save(deps.storage, quest_id, &quest)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "submit_completion")
+        .add_attribute("quest_id", quest_id.to_string())
+        .add_attribute("submitter", info.sender.to_string())
+        .add_attribute("proof", proof))
+}
+
+
+fn execute_approve_submission(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    quest_id: u64,
+    submitter: String,
+) -> Result<Response, ContractError> {
+    let submitter_addr = deps.api.addr_validate(&submitter)?;
+
+    let mut quest = load_quest(deps.storage, quest_id)?;
+
+    if info.sender != quest.creator {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ensure_not_disputed(&quest)?;
+
+    let mut submission = load_pending_submission(deps.storage, quest_id, &submitter_addr)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    if env.block.time.seconds() < submission.submitted_at + config.review_period {
+        update_reputation(deps.storage, &quest.creator, |reputation| {
+            reputation.score += REPUTATION_PROMPT_PAYOUT;
+            reputation.prompt_payouts += 1;
+        })?;
+    }
+
+    let (completer_balance, payout) =
+        approve_submission(deps.storage, &env, &mut quest, &mut submission)?;
+    let badge = mint_badge(deps.storage, &quest, &submitter_addr)?;
+    let referral = pay_referral_bonus(deps.storage, &quest, &submitter_addr)?;
+
+    Ok(Response::new()
+        .add_messages(payout)
+        .add_submessages(badge)
+        .add_events(referral)
+        .add_attribute("method", "approve_submission")
+        .add_attribute("quest_id", quest_id.to_string())
+        .add_attribute("completed_by", submitter_addr.to_string())
+        .add_attribute("reward", quest.reward_amount.to_string())
+        .add_attribute("new_balance", completer_balance.balance.to_string())
+        .add_attribute("total_earned", completer_balance.total_earned.to_string()))
+}
+
+
+fn execute_reject_submission(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    quest_id: u64,
+    submitter: String,
+    reason: String,
+) -> Result<Response, ContractError> {
+    let submitter_addr = deps.api.addr_validate(&submitter)?;
+
+    let mut quest = load_quest(deps.storage, quest_id)?;
+
+    if info.sender != quest.creator {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ensure_not_disputed(&quest)?;
+
+    let mut submission = load_pending_submission(deps.storage, quest_id, &submitter_addr)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    if env.block.time.seconds() >= submission.submitted_at + config.review_period {
+        return Err(ContractError::ReviewPeriodEnded {});
+    }
+
+    submission.status = SubmissionStatus::Rejected;
+    submission.reviewed_at = Some(env.block.time.seconds());
+    submission.rejection_reason = Some(reason.clone());
+    SUBMISSIONS.save(deps.storage, (quest_id, &submitter_addr), &submission)?;
+
+    update_reputation(deps.storage, &submitter_addr, |reputation| {
+        reputation.score += REPUTATION_REJECTED;
+        reputation.rejected += 1;
+    })?;
+
+    if quest.status == QuestStatus::Submitted && !has_pending_submissions(deps.storage, quest_id) {
+        quest.status = QuestStatus::Open;
+        QUESTS.save(deps.storage, quest_id, &quest)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "reject_submission")
+        .add_attribute("quest_id", quest_id.to_string())
+        .add_attribute("submitter", submitter_addr.to_string())
+        .add_attribute("reason", reason))
+}
+
+
+fn execute_finalize_submission(
+    deps: DepsMut,
+    env: Env,
+    quest_id: u64,
+    submitter: String,
+) -> Result<Response, ContractError> {
+    let submitter_addr = deps.api.addr_validate(&submitter)?;
+
+    let mut quest = load_quest(deps.storage, quest_id)?;
+
+    ensure_not_disputed(&quest)?;
+
+    let mut submission = load_pending_submission(deps.storage, quest_id, &submitter_addr)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let review_ends_at = submission.submitted_at + config.review_period;
+    if env.block.time.seconds() < review_ends_at {
+        return Err(ContractError::ReviewPeriodActive {
+            ends_at: review_ends_at,
+        });
+    }
+
+    let (completer_balance, payout) =
+        approve_submission(deps.storage, &env, &mut quest, &mut submission)?;
+    let badge = mint_badge(deps.storage, &quest, &submitter_addr)?;
+    let referral = pay_referral_bonus(deps.storage, &quest, &submitter_addr)?;
+
+    Ok(Response::new()
+        .add_messages(payout)
+        .add_submessages(badge)
+        .add_events(referral)
+        .add_attribute("method", "finalize_submission")
+        .add_attribute("quest_id", quest_id.to_string())
+        .add_attribute("completed_by", submitter_addr.to_string())
+        .add_attribute("reward", quest.reward_amount.to_string())
+        .add_attribute("new_balance", completer_balance.balance.to_string())
+        .add_attribute("total_earned", completer_balance.total_earned.to_string()))
+}
+
+
+fn execute_dispute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    quest_id: u64,
+    reason: String,
+) -> Result<Response, ContractError> {
+    let mut quest = load_quest(deps.storage, quest_id)?;
+
+    ensure_not_disputed(&quest)?;
+
+    if !is_active(&quest) {
+        return Err(ContractError::QuestNotActive {});
+    }
+
+    let submission = SUBMISSIONS
+        .may_load(deps.storage, (quest_id, &info.sender))?
+        .ok_or(ContractError::SubmissionNotFound {})?;
+
+    if submission.status != SubmissionStatus::Rejected {
+        return Err(ContractError::SubmissionNotRejected {});
+    }
+
+    if DISPUTES.has(deps.storage, (quest_id, &info.sender)) {
+        return Err(ContractError::DisputeExists {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let window_end = submission
+        .reviewed_at
+        .unwrap_or(submission.submitted_at)
+        .saturating_add(config.review_period);
+    if env.block.time.seconds() >= window_end {
+        return Err(ContractError::DisputeWindowClosed {});
+    }
+
+    let dispute = Dispute {
+        quest_id,
+        submitter: info.sender.clone(),
+        reason: reason.clone(),
+        status: DisputeStatus::Open,
+        winner: None,
+        opened_at: env.block.time.seconds(),
+        resolved_at: None,
+    };
+
+    quest.status = QuestStatus::Disputed;
+
+    DISPUTES.save(deps.storage, (quest_id, &info.sender), &dispute)?;
+    QUESTS.save(deps.storage, quest_id, &quest)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "dispute")
+        .add_attribute("quest_id", quest_id.to_string())
+        .add_attribute("submitter", info.sender.to_string())
+        .add_attribute("reason", reason))
+}
+
+
+fn execute_resolve_dispute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    quest_id: u64,
+    submitter: String,
+    winner: DisputeParty,
+) -> Result<Response, ContractError> {
+    let submitter_addr = deps.api.addr_validate(&submitter)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.arbiter {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut dispute = DISPUTES
+        .may_load(deps.storage, (quest_id, &submitter_addr))?
+        .ok_or(ContractError::DisputeNotFound {})?;
+
+    if dispute.status != DisputeStatus::Open {
+        return Err(ContractError::DisputeAlreadyResolved {});
+    }
+
+    let mut quest = load_quest(deps.storage, quest_id)?;
+    let now = env.block.time.seconds();
+    extend_review_clocks(deps.storage, quest_id, now.saturating_sub(dispute.opened_at))?;
+
+    let mut response = Response::new()
+        .add_attribute("method", "resolve_dispute")
+        .add_attribute("quest_id", quest_id.to_string())
+        .add_attribute("submitter", submitter_addr.to_string());
+
+    match winner {
+        DisputeParty::Submitter => {
+            let mut submission = SUBMISSIONS.load(deps.storage, (quest_id, &submitter_addr))?;
+            submission.rejection_reason = None;
+
+            update_reputation(deps.storage, &submitter_addr, |reputation| {
+                reputation.score -= REPUTATION_REJECTED;
+                reputation.rejected -= 1;
+            })?;
+            update_reputation(deps.storage, &quest.creator, |reputation| {
+                reputation.score += REPUTATION_DISPUTE_LOST;
+                reputation.disputes_lost += 1;
+            })?;
+
+            let (completer_balance, payout) =
+                approve_submission(deps.storage, &env, &mut quest, &mut submission)?;
+            let badge = mint_badge(deps.storage, &quest, &submitter_addr)?;
+            let referral = pay_referral_bonus(deps.storage, &quest, &submitter_addr)?;
+
+            response = response
+                .add_messages(payout)
+                .add_submessages(badge)
+                .add_events(referral)
+                .add_attribute("winner", "submitter")
+                .add_attribute("reward", quest.reward_amount.to_string())
+                .add_attribute("new_balance", completer_balance.balance.to_string());
+        }
+        DisputeParty::Creator => {
+            quest.status = if has_pending_submissions(deps.storage, quest_id) {
+                QuestStatus::Submitted
+            } else {
+                QuestStatus::Open
+            };
+            QUESTS.save(deps.storage, quest_id, &quest)?;
+
+            update_reputation(deps.storage, &submitter_addr, |reputation| {
+                reputation.score += REPUTATION_DISPUTE_LOST;
+                reputation.disputes_lost += 1;
+            })?;
+
+            response = response.add_attribute("winner", "creator");
+        }
+    }
+
+    dispute.status = DisputeStatus::Resolved;
+    dispute.winner = Some(winner);
+    dispute.resolved_at = Some(now);
+    DISPUTES.save(deps.storage, (quest_id, &submitter_addr), &dispute)?;
+
+    Ok(response)
+}
+
+
+fn execute_cancel_quest(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    quest_id: u64,
+) -> Result<Response, ContractError> {
+    let mut quest = load_quest(deps.storage, quest_id)?;
+
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != quest.creator && !is_owner(&config, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if quest.status != QuestStatus::Open {
+        return Err(ContractError::QuestNotActive {});
+    }
+
+    let now = env.block.time.seconds();
+    if let Some(ends_at) = open_dispute_window(deps.storage, quest_id, config.review_period, now)? {
+        return Err(ContractError::DisputeWindowOpen { ends_at });
+    }
+
+    let (refund, refund_msg) = refund_escrow(deps.storage, &quest)?;
+
+    quest.status = QuestStatus::Cancelled;
+    QUESTS.save(deps.storage, quest_id, &quest)?;
+
+    Ok(Response::new()
+        .add_messages(refund_msg)
+        .add_event(
+            Event::new("quest_cancelled")
+                .add_attribute("quest_id", quest_id.to_string())
+                .add_attribute("creator", quest.creator.to_string())
+                .add_attribute("refund", refund.to_string()),
+        )
+        .add_attribute("method", "cancel_quest")
+        .add_attribute("quest_id", quest_id.to_string())
+        .add_attribute("cancelled_by", info.sender.to_string())
+        .add_attribute("refund", refund.to_string()))
+}
+
+
+fn execute_expire_quests(
+    deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let now = env.block.time.seconds();
+    let config = CONFIG.load(deps.storage)?;
+
+    let overdue: Vec<Quest> = QUESTS
+        .idx
+        .open_deadline
+        .range(deps.storage, None, Some(Bound::exclusive((now, 0))), Order::Ascending)
+        .take(limit)
+        .map(|r| r.map(|(_, quest)| quest))
+        .collect::<StdResult<_>>()?;
+
+    let mut response = Response::new().add_attribute("method", "expire_quests");
+    for mut quest in overdue {
+        // Picked up again by a later crank once the dispute window closes.
+        if open_dispute_window(deps.storage, quest.id, config.review_period, now)?.is_some() {
+            continue;
+        }
+
+        let (refund, refund_msg) = refund_escrow(deps.storage, &quest)?;
+
+        quest.status = QuestStatus::Expired;
+        QUESTS.save(deps.storage, quest.id, &quest)?;
+
+        response = response.add_messages(refund_msg).add_event(
+            Event::new("quest_expired")
+                .add_attribute("quest_id", quest.id.to_string())
+                .add_attribute("creator", quest.creator.to_string())
+                .add_attribute("refund", refund.to_string()),
+        );
+    }
+
+    Ok(response)
+}
+
+
+fn execute_deposit(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let amount = must_pay(&info, &config.denom)?;
+
+    let mut user_balance = get_or_create_balance(deps.storage, &info.sender)?;
+    user_balance.balance += amount;
+    BALANCES.save(deps.storage, &info.sender, &user_balance)?;
+    update_supply(deps.storage, amount, Uint128::zero())?;
+
+    Ok(Response::new()
+        .add_attribute("method", "deposit")
+        .add_attribute("depositor", info.sender.to_string())
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("denom", config.denom)
+        .add_attribute("new_balance", user_balance.balance.to_string()))
+}
+
+
+fn execute_withdraw(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: String,
+) -> Result<Response, ContractError> {
+    let withdraw_amount = parse_amount(&amount, "amount")?;
+
+    if withdraw_amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut user_balance = get_or_create_balance(deps.storage, &info.sender)?;
+
+    if user_balance.balance < withdraw_amount {
+        return Err(ContractError::InsufficientBalance {
+            required: withdraw_amount,
+            available: user_balance.balance,
+        });
+    }
+
+    user_balance.balance -= withdraw_amount;
+    BALANCES.save(deps.storage, &info.sender, &user_balance)?;
+    update_supply(deps.storage, Uint128::zero(), withdraw_amount)?;
+
+    let send = BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount: coins(withdraw_amount.u128(), &config.denom),
+    };
+
+    Ok(Response::new()
+        .add_message(send)
+        .add_attribute("method", "withdraw")
+        .add_attribute("recipient", info.sender.to_string())
+        .add_attribute("amount", withdraw_amount.to_string())
+        .add_attribute("denom", config.denom)
+        .add_attribute("new_balance", user_balance.balance.to_string()))
+}
+
+
+fn execute_transfer(
+    deps: DepsMut,
+    info: MessageInfo,
+    recipient: String,
+    amount: String,
+) -> Result<Response, ContractError> {
+    let transfer_amount = parse_amount(&amount, "amount")?;
+
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+
+    let (sender_balance, recipient_balance) =
+        move_balance(deps.storage, &info.sender, &recipient_addr, transfer_amount)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "transfer")
+        .add_attribute("from", info.sender.to_string())
+        .add_attribute("to", recipient)
+        .add_attribute("amount", transfer_amount.to_string())
+        .add_attribute("sender_new_balance", sender_balance.balance.to_string())
+        .add_attribute("recipient_new_balance", recipient_balance.balance.to_string()))
+}
+
+
+fn execute_batch_transfer(
+    deps: DepsMut,
+    info: MessageInfo,
+    transfers: Vec<(String, String)>,
+) -> Result<Response, ContractError> {
+    if transfers.is_empty() {
+        return Err(ContractError::InvalidInput {
+            reason: "transfers must not be empty".to_string(),
+        });
+    }
+
+    let transfers = transfers
+        .into_iter()
+        .map(|(recipient, amount)| {
+            let recipient = deps.api.addr_validate(&recipient)?;
+            let amount = parse_amount(&amount, "amount")?;
+            Ok((recipient, amount))
+        })
+        .collect::<Result<Vec<(Addr, Uint128)>, ContractError>>()?;
+
+    let total = transfers
+        .iter()
+        .try_fold(Uint128::zero(), |total, (_, amount)| total.checked_add(*amount))
+        .map_err(StdError::overflow)?;
+
+    let mut sender_balance = get_or_create_balance(deps.storage, &info.sender)?;
+    if sender_balance.balance < total {
+        return Err(ContractError::InsufficientBalance {
+            required: total,
+            available: sender_balance.balance,
+        });
+    }
+
+    sender_balance.balance -= total;
+    BALANCES.save(deps.storage, &info.sender, &sender_balance)?;
+
+    let mut response = Response::new()
+        .add_event(
+            Event::new("batch_transfer")
+                .add_attribute("from", info.sender.to_string())
+                .add_attribute("recipients", transfers.len().to_string())
+                .add_attribute("total", total.to_string()),
+        )
+        .add_attribute("method", "batch_transfer")
+        .add_attribute("from", info.sender.to_string());
+
+    for (recipient, amount) in transfers {
+        let mut recipient_balance = get_or_create_balance(deps.storage, &recipient)?;
+        recipient_balance.balance += amount;
+        BALANCES.save(deps.storage, &recipient, &recipient_balance)?;
+
+        response = response
+            .add_attribute("to", recipient.to_string())
+            .add_attribute("amount", amount.to_string());
+    }
+
+    let sender_balance = get_or_create_balance(deps.storage, &info.sender)?;
+
+    Ok(response
+        .add_attribute("total", total.to_string())
+        .add_attribute("sender_new_balance", sender_balance.balance.to_string()))
+}
+
+
+fn execute_send(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+    amount: String,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    let send_amount = parse_amount(&amount, "amount")?;
+
+    let contract_addr = deps.api.addr_validate(&contract)?;
+
+    let (sender_balance, _) =
+        move_balance(deps.storage, &info.sender, &contract_addr, send_amount)?;
+
+    let receive = Cw20ReceiveMsg {
+        sender: info.sender.to_string(),
+        amount: send_amount,
+        msg,
+    }
+    .into_cosmos_msg(contract.clone())?;
+
+    Ok(Response::new()
+        .add_message(receive)
+        .add_attribute("method", "send")
+        .add_attribute("from", info.sender.to_string())
+        .add_attribute("to", contract)
+        .add_attribute("amount", send_amount.to_string())
+        .add_attribute("sender_new_balance", sender_balance.balance.to_string()))
+}
+
+
+fn execute_burn(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: String,
+) -> Result<Response, ContractError> {
+    let burn_amount = parse_amount(&amount, "amount")?;
+
+    let mut user_balance = get_or_create_balance(deps.storage, &info.sender)?;
+
+    if user_balance.balance < burn_amount {
+        return Err(ContractError::InsufficientBalance {
+            required: burn_amount,
+            available: user_balance.balance,
+        });
+    }
+
+    user_balance.balance -= burn_amount;
+    BALANCES.save(deps.storage, &info.sender, &user_balance)?;
+    update_supply(deps.storage, Uint128::zero(), burn_amount)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "burn")
+        .add_attribute("from", info.sender.to_string())
+        .add_attribute("amount", burn_amount.to_string())
+        .add_attribute("new_balance", user_balance.balance.to_string()))
+}
+
+
+fn execute_increase_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    amount: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    if spender_addr == info.sender {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+
+    let increase = parse_amount(&amount, "amount")?;
+
+    if let Some(expires) = &expires {
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::AllowanceExpired {});
+        }
+    }
+
+    let allowance = ALLOWANCES.update(
+        deps.storage,
+        (&info.sender, &spender_addr),
+        |current| -> StdResult<_> {
+            let mut allowance = current.unwrap_or_default();
+            if let Some(expires) = expires {
+                allowance.expires = expires;
+            }
+            allowance.allowance += increase;
+            Ok(allowance)
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "increase_allowance")
+        .add_attribute("owner", info.sender.to_string())
+        .add_attribute("spender", spender_addr.to_string())
+        .add_attribute("amount", increase.to_string())
+        .add_attribute("allowance", allowance.allowance.to_string()))
+}
+
+
+fn execute_decrease_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    amount: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    if spender_addr == info.sender {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+
+    let decrease = parse_amount(&amount, "amount")?;
+
+    let key = (&info.sender, &spender_addr);
+    let mut allowance = ALLOWANCES.may_load(deps.storage, key)?.unwrap_or_default();
+
+    if decrease < allowance.allowance {
+        if let Some(expires) = expires {
+            if expires.is_expired(&env.block) {
+                return Err(ContractError::AllowanceExpired {});
+            }
+            allowance.expires = expires;
+        }
+        allowance.allowance -= decrease;
+        ALLOWANCES.save(deps.storage, key, &allowance)?;
+    } else {
+        allowance.allowance = Uint128::zero();
+        ALLOWANCES.remove(deps.storage, key);
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "decrease_allowance")
+        .add_attribute("owner", info.sender.to_string())
+        .add_attribute("spender", spender_addr.to_string())
+        .add_attribute("amount", decrease.to_string())
+        .add_attribute("allowance", allowance.allowance.to_string()))
+}
+
+
+fn execute_transfer_from(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    recipient: String,
+    amount: String,
+) -> Result<Response, ContractError> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+    let transfer_amount = parse_amount(&amount, "amount")?;
+
+    spend_allowance(deps.storage, &env, &owner_addr, &info.sender, transfer_amount)?;
+
+    let (owner_balance, recipient_balance) =
+        move_balance(deps.storage, &owner_addr, &recipient_addr, transfer_amount)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "transfer_from")
+        .add_attribute("from", owner_addr.to_string())
+        .add_attribute("to", recipient_addr.to_string())
+        .add_attribute("by", info.sender.to_string())
+        .add_attribute("amount", transfer_amount.to_string())
+        .add_attribute("owner_new_balance", owner_balance.balance.to_string())
+        .add_attribute("recipient_new_balance", recipient_balance.balance.to_string()))
+}
+
+
+fn execute_admin_withdraw(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: String,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let withdraw_amount = parse_amount(&amount, "amount")?;
+
+    if withdraw_amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    ensure_owner(&config, &info.sender)?;
+
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => config.treasury,
+    };
+
+    let treasury = TREASURY.load(deps.storage)?;
+    if treasury < withdraw_amount {
+        return Err(ContractError::InsufficientBalance {
+            required: withdraw_amount,
+            available: treasury,
+        });
+    }
+
+    let remaining = treasury - withdraw_amount;
+    TREASURY.save(deps.storage, &remaining)?;
+
+    let mut recipient_balance = get_or_create_balance(deps.storage, &recipient)?;
+    recipient_balance.balance += withdraw_amount;
+    BALANCES.save(deps.storage, &recipient, &recipient_balance)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "admin_withdraw")
+        .add_attribute("recipient", recipient.to_string())
+        .add_attribute("amount", withdraw_amount.to_string())
+        .add_attribute("treasury_balance", remaining.to_string())
+        .add_attribute("new_balance", recipient_balance.balance.to_string()))
+}
+
+
+fn execute_add_category(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tag: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure_owner(&config, &info.sender)?;
+
+    let valid = !tag.is_empty()
+        && tag.len() <= MAX_TAG_LENGTH
+        && tag.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if !valid {
+        return Err(ContractError::InvalidInput {
+            reason: format!(
+                "tag must be 1-{} lowercase letters, digits or dashes",
+                MAX_TAG_LENGTH
+            ),
+        });
+    }
+
+    let category = match CATEGORIES.may_load(deps.storage, &tag)? {
+        Some(category) if category.active => {
+            return Err(ContractError::CategoryExists { tag });
+        }
+        Some(category) => Category { active: true, ..category },
+        None => Category {
+            tag: tag.clone(),
+            active: true,
+            created_at: env.block.time.seconds(),
+        },
+    };
+    CATEGORIES.save(deps.storage, &tag, &category)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "add_category")
+        .add_attribute("tag", tag))
+}
+
+
+fn execute_retire_category(
+    deps: DepsMut,
+    info: MessageInfo,
+    tag: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure_owner(&config, &info.sender)?;
+
+    let mut category = CATEGORIES
+        .may_load(deps.storage, &tag)?
+        .ok_or_else(|| ContractError::CategoryNotFound { tag: tag.clone() })?;
+    if !category.active {
+        return Err(ContractError::CategoryRetired { tag });
+    }
+
+    category.active = false;
+    CATEGORIES.save(deps.storage, &tag, &category)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "retire_category")
+        .add_attribute("tag", tag))
+}
+
+
+fn execute_register_referrer(
+    deps: DepsMut,
+    info: MessageInfo,
+    referrer: String,
+) -> Result<Response, ContractError> {
+    let referrer = deps.api.addr_validate(&referrer)?;
+
+    if referrer == info.sender {
+        return Err(ContractError::SelfReferral {});
+    }
+    if REFERRERS.has(deps.storage, &info.sender) {
+        return Err(ContractError::ReferrerAlreadySet {});
+    }
+    if REFERRERS.may_load(deps.storage, &referrer)?.as_ref() == Some(&info.sender) {
+        return Err(ContractError::ReferralCycle {});
+    }
+    if get_or_create_balance(deps.storage, &info.sender)?.quests_completed > 0 {
+        return Err(ContractError::NotNewUser {});
+    }
+
+    REFERRERS.save(deps.storage, &info.sender, &referrer)?;
+
+    let mut stats = REFERRAL_STATS.may_load(deps.storage, &referrer)?.unwrap_or_default();
+    stats.referrals += 1;
+    REFERRAL_STATS.save(deps.storage, &referrer, &stats)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "register_referrer")
+        .add_attribute("user", info.sender.to_string())
+        .add_attribute("referrer", referrer.to_string())
+        .add_attribute("referrals", stats.referrals.to_string()))
+}
+
+
+fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: UpdateConfigMsg,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_owner(&config, &info.sender)?;
+
+    let UpdateConfigMsg {
+        arbiter,
+        review_period,
+        fee_model,
+        treasury,
+        badge_nft_contract,
+        clear_badge_nft_contract,
+        max_name_length,
+        max_description_length,
+        referral_bonus_bps,
+    } = msg;
+
+    let mut response = Response::new().add_attribute("method", "update_config");
+
+    if let Some(arbiter) = arbiter {
+        let arbiter = deps.api.addr_validate(&arbiter)?;
+        response = response
+            .add_attribute("old_arbiter", config.arbiter.to_string())
+            .add_attribute("new_arbiter", arbiter.to_string());
+        config.arbiter = arbiter;
+    }
+
+    if let Some(review_period) = review_period {
+        ensure_valid_review_period(review_period)?;
+        response = response
+            .add_attribute("old_review_period", config.review_period.to_string())
+            .add_attribute("new_review_period", review_period.to_string());
+        config.review_period = review_period;
+    }
+
+    if let Some(fee_model) = fee_model {
+        fee_model.validate()?;
+        response = response
+            .add_attribute("old_fee_model", to_json_string(&config.fee_model)?)
+            .add_attribute("new_fee_model", to_json_string(&fee_model)?);
+        config.fee_model = fee_model;
+    }
+
+    if let Some(treasury) = treasury {
+        let treasury = deps.api.addr_validate(&treasury)?;
+        response = response
+            .add_attribute("old_treasury", config.treasury.to_string())
+            .add_attribute("new_treasury", treasury.to_string());
+        config.treasury = treasury;
+    }
+
+    let clear_badge_nft_contract = clear_badge_nft_contract.unwrap_or(false);
+    if clear_badge_nft_contract && badge_nft_contract.is_some() {
+        return Err(ContractError::InvalidInput {
+            reason: "badge_nft_contract cannot be set and cleared at once".to_string(),
+        });
+    }
+
+    let new_badge_nft_contract = match badge_nft_contract {
+        Some(contract) => Some(Some(deps.api.addr_validate(&contract)?)),
+        None if clear_badge_nft_contract => Some(None),
+        None => None,
+    };
+    if let Some(contract) = new_badge_nft_contract {
+        let describe = |contract: &Option<Addr>| {
+            contract.as_ref().map_or("none".to_string(), Addr::to_string)
+        };
+        response = response
+            .add_attribute("old_badge_nft_contract", describe(&config.badge_nft_contract))
+            .add_attribute("new_badge_nft_contract", describe(&contract));
+        config.badge_nft_contract = contract;
+    }
+
+    if let Some(limit) = max_name_length {
+        ensure_valid_limit(limit, "max_name_length")?;
+        response = response
+            .add_attribute("old_max_name_length", config.max_name_length.to_string())
+            .add_attribute("new_max_name_length", limit.to_string());
+        config.max_name_length = limit;
+    }
+
+    if let Some(limit) = max_description_length {
+        ensure_valid_limit(limit, "max_description_length")?;
+        response = response
+            .add_attribute(
+                "old_max_description_length",
+                config.max_description_length.to_string(),
+            )
+            .add_attribute("new_max_description_length", limit.to_string());
+        config.max_description_length = limit;
+    }
+
+    if let Some(bps) = referral_bonus_bps {
+        ensure_valid_bps(bps, "referral_bonus_bps")?;
+        response = response
+            .add_attribute("old_referral_bonus_bps", config.referral_bonus_bps.to_string())
+            .add_attribute("new_referral_bonus_bps", bps.to_string());
+        config.referral_bonus_bps = bps;
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(response)
+}
+
+
+fn execute_propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    expiry: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure_owner(&config, &info.sender)?;
+
+    let new_owner = deps.api.addr_validate(&owner)?;
+
+    if let Some(expiry) = &expiry {
+        if expiry.is_expired(&env.block) {
+            return Err(ContractError::OwnershipProposalExpired {});
+        }
+    }
+
+    PENDING_OWNER.save(
+        deps.storage,
+        &PendingOwner {
+            owner: new_owner.clone(),
+            expiry,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "propose_new_owner")
+        .add_attribute("current_owner", info.sender.to_string())
+        .add_attribute("pending_owner", new_owner.to_string()))
+}
+
+
+fn execute_accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pending = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingOwner {})?;
+
+    if pending.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(expiry) = &pending.expiry {
+        if expiry.is_expired(&env.block) {
+            return Err(ContractError::OwnershipProposalExpired {});
+        }
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let previous_owner = config.owner.replace(pending.owner.clone());
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("method", "accept_ownership")
+        .add_attribute(
+            "previous_owner",
+            previous_owner.map(String::from).unwrap_or_default(),
+        )
+        .add_attribute("new_owner", pending.owner.to_string()))
+}
+
+
+fn execute_renounce_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_owner(&config, &info.sender)?;
+
+    config.owner = None;
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("method", "renounce_ownership")
+        .add_attribute("previous_owner", info.sender.to_string()))
+}
+
+
+// ============= QUERY HANDLERS =============
+
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    let binary = match msg {
+        QueryMsg::GetConfig {} => to_json_binary(&query_config(deps)?)?,
+        QueryMsg::GetBalance { address } => to_json_binary(&query_balance(deps, address)?)?,
+        QueryMsg::GetQuest { quest_id } => to_json_binary(&query_quest(deps, quest_id)?)?,
+        QueryMsg::GetActiveQuests { start_after, limit } => {
+            to_json_binary(&query_active_quests(deps, env, start_after, limit)?)?
+        }
+        QueryMsg::GetUserQuests { address, start_after, limit } => {
+            to_json_binary(&query_user_quests(deps, address, start_after, limit)?)?
+        }
+        QueryMsg::GetCompletedQuests { address, start_after, limit } => {
+            to_json_binary(&query_completed_quests(deps, address, start_after, limit)?)?
+        }
+        QueryMsg::ListQuests { status, creator, min_reward, start_after, limit } => {
+            to_json_binary(&query_list_quests(deps, status, creator, min_reward, start_after, limit)?)?
+        }
+        QueryMsg::GetUserStats { address } => to_json_binary(&query_user_stats(deps, address)?)?,
+        QueryMsg::GetPendingSubmissions { quest_id } => {
+            to_json_binary(&query_pending_submissions(deps, quest_id)?)?
+        }
+        QueryMsg::GetDisputes { status, start_after, limit } => {
+            to_json_binary(&query_disputes(deps, status, start_after, limit)?)?
+        }
+        QueryMsg::QuestsByTag { tag, start_after, limit } => {
+            to_json_binary(&query_quests_by_tag(deps, tag, start_after, limit)?)?
+        }
+        QueryMsg::GetCategories {} => to_json_binary(&query_categories(deps)?)?,
+        QueryMsg::GetReputation { address } => to_json_binary(&query_reputation(deps, address)?)?,
+        QueryMsg::GetReferrals { address } => to_json_binary(&query_referrals(deps, address)?)?,
+        QueryMsg::Leaderboard { metric, start_after, limit } => {
+            to_json_binary(&query_leaderboard(deps, metric, start_after, limit)?)?
+        }
+        QueryMsg::SimulateCreateQuest { reward_amount, max_completions, reward_asset } => {
+            to_json_binary(&query_simulate_create_quest(
+                deps,
+                reward_amount,
+                max_completions,
+                reward_asset.unwrap_or(Asset::Internal),
+            )?)?
+        }
+        QueryMsg::Balance { address } => to_json_binary(&query_cw20_balance(deps, address)?)?,
+        QueryMsg::TokenInfo {} => to_json_binary(&query_token_info(deps)?)?,
+        QueryMsg::Allowance { owner, spender } => {
+            to_json_binary(&query_allowance(deps, env, owner, spender)?)?
+        }
+        QueryMsg::AllAccounts { start_after, limit } => {
+            to_json_binary(&query_all_accounts(deps, start_after, limit)?)?
+        }
+    };
+
+    Ok(binary)
+}
+
+
+fn query_config(deps: Deps) -> StdResult<GetConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(GetConfigResponse {
+        owner: config.owner,
+        pending_owner: PENDING_OWNER.may_load(deps.storage)?,
+        arbiter: config.arbiter,
+        treasury: config.treasury,
+        badge_nft_contract: config.badge_nft_contract,
+        denom: config.denom,
+        fee_model: config.fee_model,
+        review_period: config.review_period,
+        max_name_length: config.max_name_length,
+        max_description_length: config.max_description_length,
+        referral_bonus_bps: config.referral_bonus_bps,
+        total_quests: config.total_quests,
+        total_completed: config.total_completed,
+        total_fees_collected: config.total_fees_collected,
+        treasury_balance: TREASURY.load(deps.storage)?,
+    })
+}
+
+
+fn query_quests_by_tag(
+    deps: Deps,
+    tag: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<QuestsResponse> {
+    let quests = QUEST_TAGS
+        .prefix(&tag)
+        .keys(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .filter_map(|quest_id| quest_id.ok())
+        .filter_map(|quest_id| QUESTS.may_load(deps.storage, quest_id).ok().flatten());
+
+    let (quests, next_key) = collect_page(quests, limit);
+
+    Ok(QuestsResponse { quests, next_key })
+}
+
+
+fn query_categories(deps: Deps) -> StdResult<CategoriesResponse> {
+    let categories = CATEGORIES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|r| r.map(|(_, category)| category))
+        .collect::<StdResult<_>>()?;
+
+    Ok(CategoriesResponse { categories })
+}
+
+
+fn query_reputation(deps: Deps, address: String) -> StdResult<Reputation> {
+    let addr = deps.api.addr_validate(&address)?;
+    Ok(REPUTATION.may_load(deps.storage, &addr)?.unwrap_or_default())
+}
+
+
+fn query_referrals(deps: Deps, address: String) -> StdResult<ReferralsResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let stats = REFERRAL_STATS.may_load(deps.storage, &addr)?.unwrap_or_default();
+
+    Ok(ReferralsResponse {
+        referrer: REFERRERS.may_load(deps.storage, &addr)?,
+        address: addr,
+        referrals: stats.referrals,
+        total_earned: stats.total_earned,
+    })
+}
+
+
+fn query_leaderboard(
+    deps: Deps,
+    metric: LeaderboardMetric,
+    start_after: Option<(Uint128, String)>,
+    limit: Option<u32>,
+) -> StdResult<LeaderboardResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    // The cursor carries the score it was ranked at: re-reading the address's
+    // current score would skip or repeat entries once that score changes.
+    let start = start_after
+        .map(|(score, address)| deps.api.addr_validate(&address).map(|addr| (score, addr)))
+        .transpose()?;
+
+    let ranked: Box<dyn Iterator<Item = StdResult<(Addr, UserBalance)>>> = match metric {
+        LeaderboardMetric::TotalEarned => BALANCES.idx.total_earned.range(
+            deps.storage,
+            None,
+            start.map(|(score, addr)| Bound::exclusive((score.u128(), addr))),
+            Order::Descending,
+        ),
+        LeaderboardMetric::QuestsCompleted => {
+            let start = start
+                .map(|(score, addr)| {
+                    u64::try_from(score.u128())
+                        .map(|score| Bound::exclusive((score, addr)))
+                        .map_err(|_| StdError::generic_err("Leaderboard cursor score is out of range"))
+                })
+                .transpose()?;
+            BALANCES.idx.quests_completed.range(deps.storage, None, start, Order::Descending)
+        }
+    };
+
+    let entries: Vec<LeaderboardEntry> = ranked
+        .take(limit)
+        .map(|r| {
+            r.map(|(address, balance)| LeaderboardEntry {
+                address,
+                total_earned: balance.total_earned,
+                quests_completed: balance.quests_completed,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    let next_key = if entries.len() == limit {
+        entries.last().map(|entry| {
+            let score = match metric {
+                LeaderboardMetric::TotalEarned => entry.total_earned,
+                LeaderboardMetric::QuestsCompleted => Uint128::from(entry.quests_completed),
+            };
+            (score, entry.address.clone())
+        })
+    } else {
+        None
+    };
+
+    Ok(LeaderboardResponse { entries, next_key })
+}
+
+
+fn query_simulate_create_quest(
+    deps: Deps,
+    reward_amount: String,
+    max_completions: Option<u64>,
+    reward_asset: Asset,
+) -> Result<SimulateCreateQuestResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let reward = parse_amount(&reward_amount, "reward_amount")?;
+    let (_, escrow) = quest_escrow(reward, max_completions)?;
+    let fee = config.fee_model.fee_for_asset(&reward_asset, escrow);
+    let total_cost = match reward_asset {
+        Asset::Internal => fee + escrow,
+        Asset::Native { .. } | Asset::Cw20 { .. } => fee,
+    };
+
+    Ok(SimulateCreateQuestResponse {
+        fee,
+        escrow,
+        total_cost,
+    })
+}
+
+
+fn query_balance(deps: Deps, address: String) -> StdResult<GetBalanceResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let balance = get_or_create_balance(deps.storage, &addr)?;
+
+    Ok(GetBalanceResponse {
+        address: addr,
+        balance: balance.balance,
+        total_earned: balance.total_earned,
+    })
+}
+
+
+fn query_quest(deps: Deps, quest_id: u64) -> Result<GetQuestResponse, ContractError> {
+    let quest = load_quest(deps.storage, quest_id)?;
+    // A disputed quest still holds its escrow, so its slots stay claimable.
+    let remaining_slots = match quest.status {
+        QuestStatus::Cancelled | QuestStatus::Expired => 0,
+        _ => quest.max_completions.saturating_sub(quest.completions),
+    };
+    Ok(GetQuestResponse { quest, remaining_slots })
+}
+
+
+fn collect_page(
+    quests: impl Iterator<Item = Quest>,
+    limit: Option<u32>,
+) -> (Vec<Quest>, Option<u64>) {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let page: Vec<Quest> = quests.take(limit).collect();
+    let next_key = if page.len() == limit {
+        page.last().map(|quest| quest.id)
+    } else {
+        None
+    };
+    (page, next_key)
+}
+
+
+/// Filters the index can't serve are applied while scanning, and at most
+/// `MAX_QUEST_SCAN` quests are visited, so a selective filter may return a
+/// short (even empty) page; `next_key` then points at the last quest scanned
+/// rather than the last one returned.
+fn scan_page(
+    candidates: impl Iterator<Item = Quest>,
+    matches: impl Fn(&Quest) -> bool,
+    limit: Option<u32>,
+) -> (Vec<Quest>, Option<u64>) {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let mut quests = Vec::new();
+    let mut scanned = 0;
+    let mut last_scanned = None;
+    for quest in candidates.take(MAX_QUEST_SCAN) {
+        scanned += 1;
+        last_scanned = Some(quest.id);
+        if matches(&quest) {
+            quests.push(quest);
+            if quests.len() == limit {
+                break;
+            }
+        }
+    }
+
+    let next_key = if quests.len() == limit || scanned == MAX_QUEST_SCAN {
+        last_scanned
+    } else {
+        None
+    };
+    (quests, next_key)
+}
+
+
+fn quests_with_status<'a>(
+    storage: &'a dyn cosmwasm_std::Storage,
+    status: &QuestStatus,
+    start_after: Option<u64>,
+) -> Box<dyn Iterator<Item = Quest> + 'a> {
+    Box::new(
+        QUESTS
+            .idx
+            .status
+            .prefix(status.as_str().to_string())
+            .range(storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+            .filter_map(|r| r.ok().map(|(_, quest)| quest)),
+    )
+}
+
+
+fn query_active_quests(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<GetActiveQuestsResponse> {
+    let now = env.block.time.seconds();
+    let page_size = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // Open and Submitted quests come from separate index prefixes. Each scan
+    // stops at its own `next_key`, so the merged page may only reach as far as
+    // the nearer of the two or it would skip quests the other scan never saw.
+    let mut active = Vec::new();
+    let mut scanned_up_to: Option<u64> = None;
+    for status in [QuestStatus::Open, QuestStatus::Submitted] {
+        let (quests, next_key) = scan_page(
+            quests_with_status(deps.storage, &status, start_after),
+            |quest| !is_overdue(quest, now),
+            limit,
+        );
+        active.extend(quests);
+        if let Some(next_key) = next_key {
+            scanned_up_to = Some(scanned_up_to.map_or(next_key, |id| id.min(next_key)));
+        }
+    }
+    active.retain(|quest| scanned_up_to.is_none_or(|id| quest.id <= id));
+    active.sort_by_key(|quest| quest.id);
+    active.truncate(page_size);
+
+    let next_key = if active.len() == page_size {
+        active.last().map(|quest| quest.id)
+    } else {
+        scanned_up_to
+    };
+
+    let count = active.len() as u64;
+    Ok(GetActiveQuestsResponse { quests: active, count, next_key })
+}
+
+
+fn query_user_quests(
+    deps: Deps,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<QuestsResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+
+    let (quests, next_key) = collect_page(
+        QUESTS
+            .idx
+            .creator
+            .prefix(addr)
+            .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+            .filter_map(|r| r.ok().map(|(_, quest)| quest)),
+        limit,
+    );
+
+    Ok(QuestsResponse { quests, next_key })
+}
+
+
+fn query_completed_quests(
+    deps: Deps,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<QuestsResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let start = start_after.map(|id| Bound::exclusive((id, addr.clone())));
+
+    let (quests, next_key) = collect_page(
+        COMPLETIONS
+            .idx
+            .completed_by
+            .prefix(addr.clone())
+            .range(deps.storage, start, None, Order::Ascending)
+            .filter_map(|r| r.ok())
+            .filter_map(|(_, completion)| QUESTS.load(deps.storage, completion.quest_id).ok()),
+        limit,
+    );
+
+    Ok(QuestsResponse { quests, next_key })
+}
+
+
+fn query_list_quests(
+    deps: Deps,
+    status: Option<QuestStatus>,
+    creator: Option<String>,
+    min_reward: Option<String>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<QuestsResponse, ContractError> {
+    let creator = creator
+        .map(|creator| deps.api.addr_validate(&creator))
+        .transpose()?;
+    let min_reward = min_reward
+        .map(|amount| parse_amount(&amount, "min_reward"))
+        .transpose()?;
+    let start = start_after.map(Bound::exclusive);
+
+    let candidates: Box<dyn Iterator<Item = Quest>> = match (&status, &creator) {
+        (Some(status), _) => quests_with_status(deps.storage, status, start_after),
+        (None, Some(creator)) => Box::new(
+            QUESTS
+                .idx
+                .creator
+                .prefix(creator.clone())
+                .range(deps.storage, start, None, Order::Ascending)
+                .filter_map(|r| r.ok().map(|(_, quest)| quest)),
+        ),
+        (None, None) => Box::new(
+            QUESTS
+                .range(deps.storage, start, None, Order::Ascending)
+                .filter_map(|r| r.ok().map(|(_, quest)| quest)),
+        ),
+    };
+
+    let (quests, next_key) = scan_page(
+        candidates,
+        |quest| {
+            creator.as_ref().is_none_or(|creator| quest.creator == *creator)
+                && min_reward.is_none_or(|min| quest.reward_amount >= min)
+        },
+        limit,
+    );
+
+    Ok(QuestsResponse { quests, next_key })
+}
+
+
+fn query_user_stats(deps: Deps, address: String) -> StdResult<GetUserStatsResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let balance = get_or_create_balance(deps.storage, &addr)?;
+
+    Ok(GetUserStatsResponse {
+        address: addr,
+        balance: balance.balance,
+        total_earned: balance.total_earned,
+        quests_created: balance.quests_created,
+        quests_completed: balance.quests_completed,
+    })
+}
+
+
+fn query_pending_submissions(deps: Deps, quest_id: u64) -> StdResult<GetSubmissionsResponse> {
+    let submissions: Vec<Submission> = SUBMISSIONS
+        .prefix(quest_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter_map(|r| r.ok().map(|(_, submission)| submission))
+        .filter(|submission| submission.status == SubmissionStatus::Pending)
+        .collect();
+
+    Ok(GetSubmissionsResponse { submissions })
+}
+
+
+fn query_disputes(
+    deps: Deps,
+    status: Option<DisputeStatus>,
+    start_after: Option<(u64, String)>,
+    limit: Option<u32>,
+) -> StdResult<GetDisputesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|(quest_id, submitter)| deps.api.addr_validate(&submitter).map(|addr| (quest_id, addr)))
+        .transpose()?;
+
+    let disputes: Vec<Dispute> = match status {
+        Some(status) => DISPUTES
+            .idx
+            .status
+            .prefix(status.as_str().to_string())
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|r| r.map(|(_, dispute)| dispute))
+            .collect::<StdResult<_>>()?,
+        None => DISPUTES
+            .range(
+                deps.storage,
+                start_after
+                    .as_ref()
+                    .map(|(quest_id, submitter)| Bound::exclusive((*quest_id, submitter))),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|r| r.map(|(_, dispute)| dispute))
+            .collect::<StdResult<_>>()?,
+    };
+
+    let next_key = if disputes.len() == limit {
+        disputes
+            .last()
+            .map(|dispute| (dispute.quest_id, dispute.submitter.clone()))
+    } else {
+        None
+    };
+
+    Ok(GetDisputesResponse { disputes, next_key })
+}
+
+
+fn query_cw20_balance(deps: Deps, address: String) -> StdResult<BalanceResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let balance = get_or_create_balance(deps.storage, &addr)?;
+    Ok(BalanceResponse { balance: balance.balance })
+}
+
+
+fn query_allowance(
+    deps: Deps,
+    env: Env,
+    owner: String,
+    spender: String,
+) -> StdResult<AllowanceResponse> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+
+    let allowance = ALLOWANCES
+        .may_load(deps.storage, (&owner_addr, &spender_addr))?
+        .filter(|allowance| !allowance.expires.is_expired(&env.block))
+        .unwrap_or_default();
+
+    Ok(AllowanceResponse {
+        allowance: allowance.allowance,
+        expires: allowance.expires,
+    })
+}
+
+
+fn query_token_info(deps: Deps) -> StdResult<TokenInfoResponse> {
+    let info = TOKEN_INFO.load(deps.storage)?;
+    Ok(TokenInfoResponse {
+        name: info.name,
+        symbol: info.symbol,
+        decimals: info.decimals,
+        total_supply: info.total_supply,
+    })
+}
+
+
+fn query_all_accounts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllAccountsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = start_after.map(Addr::unchecked);
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    let accounts = BALANCES
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|key| key.map(String::from))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AllAccountsResponse { accounts })
+}
+
+
+// ============= MIGRATIONS =============
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct ConfigV0_1 {
+    owner: Addr,
+    quest_creation_fee: Uint128,
+    total_quests: u64,
+    total_completed: u64,
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct QuestV0_1 {
+    id: u64,
+    creator: Addr,
+    name: String,
+    description: String,
+    reward_amount: Uint128,
+    completed: bool,
+    completed_by: Option<Addr>,
+    created_at: u64,
+    completed_at: Option<u64>,
+}
+
+
+/// 0.1 balances were minted without coins behind them, while `Withdraw` pays
+/// out real coins. Refuse to migrate until the contract holds enough `denom`
+/// to back the whole legacy supply.
+fn ensure_supply_backed(deps: Deps, env: &Env) -> Result<(), ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    let held = deps
+        .querier
+        .query_balance(&env.contract.address, &config.denom)?
+        .amount;
+
+    if held < supply {
+        return Err(ContractError::InsufficientBalance {
+            required: supply,
+            available: held,
+        });
+    }
+    Ok(())
+}
+
+
+const CONFIG_V0_1: Item<ConfigV0_1> = Item::new("config");
+const QUESTS_V0_1: Map<u64, QuestV0_1> = Map::new("quests");
+const USER_QUESTS_V0_1: Map<&Addr, Vec<u64>> = Map::new("user_quests");
+
+
+fn migrate_v0_1_to_v0_2(
+    storage: &mut dyn cosmwasm_std::Storage,
+    env: &Env,
+    msg: MigrateMsg,
+) -> Result<u64, ContractError> {
+    let legacy_config = CONFIG_V0_1.load(storage)?;
+    let denom = msg.denom.ok_or(ContractError::InvalidInput {
+        reason: "denom is required when migrating from 0.1".to_string(),
+    })?;
+
+    CONFIG.save(
+        storage,
+        &Config {
+            owner: Some(legacy_config.owner.clone()),
+            arbiter: legacy_config.owner.clone(),
+            treasury: legacy_config.owner,
+            badge_nft_contract: None,
+            denom,
+            fee_model: FeeModel::Flat {
+                amount: legacy_config.quest_creation_fee,
+            },
+            review_period: DEFAULT_REVIEW_PERIOD,
+            max_name_length: DEFAULT_MAX_NAME_LENGTH,
+            max_description_length: DEFAULT_MAX_DESCRIPTION_LENGTH,
+            referral_bonus_bps: 0,
+            total_quests: legacy_config.total_quests,
+            total_completed: legacy_config.total_completed,
+            total_fees_collected: Uint128::zero(),
+        },
+    )?;
+    TREASURY.save(storage, &Uint128::zero())?;
+
+    let legacy_quests: Vec<QuestV0_1> = QUESTS_V0_1
+        .range(storage, None, None, Order::Ascending)
+        .map(|r| r.map(|(_, quest)| quest))
+        .collect::<StdResult<_>>()?;
+
+    let migrated = legacy_quests.len() as u64;
+    for legacy in legacy_quests {
+        let mut quest = Quest {
+            id: legacy.id,
+            creator: legacy.creator,
+            name: legacy.name,
+            description: legacy.description,
+            reward_asset: Asset::Internal,
+            reward_amount: legacy.reward_amount,
+            max_completions: 1,
+            completions: if legacy.completed { 1 } else { 0 },
+            status: if legacy.completed { QuestStatus::Completed } else { QuestStatus::Open },
+            completed_by: legacy.completed_by.clone(),
+            created_at: legacy.created_at,
+            completed_at: legacy.completed_at,
+            deadline: None,
+            tags: vec![],
+            min_reputation: None,
+            badge: None,
+        };
+
+        if let (Some(completer), Some(completed_at)) = (legacy.completed_by, legacy.completed_at) {
+            COMPLETIONS.save(
+                storage,
+                (quest.id, &completer),
+                &Completion {
+                    quest_id: quest.id,
+                    completer: completer.clone(),
+                    completed_at,
+                },
+            )?;
+        }
+
+        // Open 0.1 quests were never escrowed: fund them from the creator now,
+        // or cancel them if the creator can no longer cover the reward.
+        if quest.status == QuestStatus::Open {
+            let mut creator_balance = get_or_create_balance(storage, &quest.creator)?;
+            if creator_balance.balance >= quest.reward_amount {
+                creator_balance.balance -= quest.reward_amount;
+                BALANCES.save(storage, &quest.creator, &creator_balance)?;
+                ESCROW.save(storage, quest.id, &quest.reward_amount)?;
+            } else {
+                quest.status = QuestStatus::Cancelled;
+                quest.completed_at = Some(env.block.time.seconds());
+            }
+        }
+
+        QUESTS_V0_1.remove(storage, quest.id);
+        QUESTS.save(storage, quest.id, &quest)?;
+    }
+
+    let legacy_creators: Vec<Addr> = USER_QUESTS_V0_1
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    for creator in legacy_creators {
+        USER_QUESTS_V0_1.remove(storage, &creator);
+    }
+
+    // 0.1 balances were written without leaderboard indexes; re-save to build them.
+    let balances: Vec<(Addr, UserBalance)> = BALANCES
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    for (address, balance) in &balances {
+        BALANCES.save(storage, address, balance)?;
+    }
+
+    let total_supply = balances
+        .iter()
+        .map(|(_, balance)| balance.balance)
+        .sum::<Uint128>()
+        + ESCROW
+            .range(storage, None, None, Order::Ascending)
+            .map(|r| r.map(|(_, amount)| amount))
+            .sum::<StdResult<Uint128>>()?;
+
+    TOKEN_INFO.save(
+        storage,
+        &TokenInfo {
+            name: DEFAULT_TOKEN_NAME.to_string(),
+            symbol: DEFAULT_TOKEN_SYMBOL.to_string(),
+            decimals: DEFAULT_TOKEN_DECIMALS,
+            total_supply,
+        },
+    )?;
+
+    Ok(migrated)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_dependencies_with_balance, mock_env};
+    use cosmwasm_std::{coins, Addr, Empty, Uint128};
+    use cw_storage_plus::Map;
+    use cw_multi_test::{App, ContractWrapper, Executor};
+    use cw721::{NftInfoResponse, OwnerOfResponse};
+    use cw721_metadata_onchain::Cw721MetadataContract;
+
+    fn setup_contract_for_user(user: &str) -> (App, Addr, Addr) {
+        let mut app = App::new(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked(user), coins(1000, "uatom"))
+                .unwrap();
+        });
+        let code = ContractWrapper::new(execute, instantiate, query).with_reply(reply);
+        let code_id = app.store_code(Box::new(code));
+
+        let user_addr = Addr::unchecked(user);
+
+        let msg = InstantiateMsg {
+            owner: None,
+            arbiter: Some("arbiter".to_string()),
+            treasury: None,
+            badge_nft_contract: None,
+            denom: "uatom".to_string(),
+            quest_creation_fee: Some("5".to_string()),
+            fee_model: None,
+            initial_balance: "1000".to_string(),
+            review_period: Some(100),
+            max_name_length: None,
+            max_description_length: None,
+            referral_bonus_bps: None,
+            token_name: None,
+            token_symbol: None,
+            token_decimals: None,
+        };
+
+        let contract_addr = app
+            .instantiate_contract(
+                code_id,
+                user_addr.clone(),
+                &msg,
+                &coins(1000, "uatom"),
+                "quest-contract",
+                None,
+            )
+            .unwrap();
+
+        (app, contract_addr, user_addr)
+    }
+
+    fn contract_error<T: std::fmt::Debug>(result: anyhow::Result<T>) -> ContractError {
+        result.unwrap_err().downcast().unwrap()
+    }
+
+    fn create_quest_msg(reward: &str) -> ExecuteMsg {
+        ExecuteMsg::CreateQuest(CreateQuestMsg {
+            name: "Test Quest".to_string(),
+            description: "A test quest".to_string(),
+            reward_amount: reward.to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_instantiate() {
+        let (_, _, _) = setup_contract_for_user("creator");
+    }
+
+    #[test]
+    fn test_create_quest() {
+        let (mut app, contract_addr, user) = setup_contract_for_user("creator");
+
+        let msg = ExecuteMsg::CreateQuest(CreateQuestMsg {
+            name: "Test Quest".to_string(),
+            description: "A test quest".to_string(),
+            reward_amount: "100".to_string(),
+            ..Default::default()
+        });
+
+        let result = app.execute_contract(user.clone(), contract_addr.clone(), &msg, &[]);
+        assert!(result.is_ok(), "Failed to create quest");
+
+        let balance_query = QueryMsg::GetBalance {
+            address: user.to_string(),
+        };
+        let balance: GetBalanceResponse =
+            app.wrap().query_wasm_smart(&contract_addr, &balance_query).unwrap();
+
+        assert_eq!(
+            balance.balance, Uint128::new(895),
+            "Balance should be 895 after fee and reward escrow"
+        );
+    }
+
+    #[test]
+    fn test_create_quest_requires_reward_coverage() {
+        let (mut app, contract_addr, user) = setup_contract_for_user("creator");
+
+        let msg = ExecuteMsg::CreateQuest(CreateQuestMsg {
+            name: "Too Expensive".to_string(),
+            description: "Reward exceeds balance".to_string(),
+            reward_amount: "996".to_string(),
+            ..Default::default()
+        });
+
+        let result = app.execute_contract(user, contract_addr, &msg, &[]);
+        assert_eq!(
+            contract_error(result),
+            ContractError::InsufficientBalance {
+                required: Uint128::new(1001),
+                available: Uint128::new(1000),
+            },
+            "Reward plus fee must be covered by creator"
+        );
+    }
+
+    #[test]
+    fn test_owner_updates_config() {
+        let (mut app, contract_addr, owner) = setup_contract_for_user("creator");
+
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            arbiter: Some("moderator".to_string()),
+            review_period: Some(60),
+            fee_model: Some(FeeModel::Flat { amount: Uint128::new(20) }),
+            treasury: Some("treasury".to_string()),
+            max_name_length: Some(8),
+            ..Default::default()
+        });
+
+        let result = app.execute_contract(Addr::unchecked("stranger"), contract_addr.clone(), &msg, &[]);
+        assert_eq!(contract_error(result), ContractError::Unauthorized {}, "Only owner may update config");
+
+        let res = app
+            .execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let wasm = res.events.iter().find(|e| e.ty == "wasm").unwrap();
+        let attr = |key: &str| {
+            wasm.attributes
+                .iter()
+                .find(|a| a.key == key)
+                .map(|a| a.value.clone())
+        };
+        assert_eq!(attr("old_arbiter").as_deref(), Some("arbiter"));
+        assert_eq!(attr("new_arbiter").as_deref(), Some("moderator"));
+        assert_eq!(attr("old_review_period").as_deref(), Some("100"));
+        assert_eq!(attr("new_review_period").as_deref(), Some("60"));
+        assert_eq!(attr("old_fee_model").as_deref(), Some(r#"{"flat":{"amount":"5"}}"#));
+        assert_eq!(attr("new_fee_model").as_deref(), Some(r#"{"flat":{"amount":"20"}}"#));
+        assert_eq!(attr("old_treasury").as_deref(), Some("creator"));
+        assert_eq!(attr("new_treasury").as_deref(), Some("treasury"));
+        assert_eq!(attr("new_max_name_length").as_deref(), Some("8"));
+        assert_eq!(attr("old_max_description_length"), None);
+
+        let config: GetConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(config.arbiter, Addr::unchecked("moderator"));
+        assert_eq!(config.review_period, 60);
+        assert_eq!(config.fee_model, FeeModel::Flat { amount: Uint128::new(20) });
+        assert_eq!(config.treasury, Addr::unchecked("treasury"));
+        assert_eq!(config.max_name_length, 8);
+        assert_eq!(config.max_description_length, DEFAULT_MAX_DESCRIPTION_LENGTH);
+
+        let result = app.execute_contract(owner.clone(), contract_addr.clone(), &create_quest_msg("100"), &[]);
+        assert_eq!(
+            contract_error(result),
+            ContractError::ContentTooLong {
+                field: "name".to_string(),
+                max: 8,
+            },
+            "Quest name exceeds the new limit"
+        );
+
+        let msg = ExecuteMsg::CreateQuest(CreateQuestMsg {
+            name: "Short".to_string(),
+            description: "Within limits".to_string(),
+            reward_amount: "100".to_string(),
+            ..Default::default()
+        });
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let balance: GetBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetBalance { address: owner.to_string() },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(880));
+
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            review_period: Some(MAX_REVIEW_PERIOD + 1),
+            ..Default::default()
+        });
+        let result = app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[]);
+        assert!(matches!(contract_error(result), ContractError::InvalidInput { .. }));
+
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            badge_nft_contract: Some("badges".to_string()),
+            ..Default::default()
+        });
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            badge_nft_contract: Some("badges".to_string()),
+            clear_badge_nft_contract: Some(true),
+            ..Default::default()
+        });
+        let result = app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[]);
+        assert!(matches!(contract_error(result), ContractError::InvalidInput { .. }));
+
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            clear_badge_nft_contract: Some(true),
+            ..Default::default()
+        });
+        app.execute_contract(owner, contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let config: GetConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(config.badge_nft_contract, None);
+    }
+
+    #[test]
+    fn test_fee_models() {
+        let percentage = FeeModel::Percentage {
+            bps: 250,
+            min: Uint128::new(2),
+            max: Some(Uint128::new(50)),
+        };
+        assert_eq!(percentage.fee_for(Uint128::new(40)), Uint128::new(2));
+        assert_eq!(percentage.fee_for(Uint128::new(1000)), Uint128::new(25));
+        assert_eq!(percentage.fee_for(Uint128::new(10_000)), Uint128::new(50));
+
+        let tiered = FeeModel::Tiered {
+            tiers: vec![
+                FeeTier { min_reward: Uint128::zero(), fee: Uint128::new(1) },
+                FeeTier { min_reward: Uint128::new(100), fee: Uint128::new(5) },
+                FeeTier { min_reward: Uint128::new(1000), fee: Uint128::new(20) },
+            ],
+        };
+        assert_eq!(tiered.fee_for(Uint128::new(99)), Uint128::new(1));
+        assert_eq!(tiered.fee_for(Uint128::new(100)), Uint128::new(5));
+        assert_eq!(tiered.fee_for(Uint128::new(5000)), Uint128::new(20));
+
+        let unsorted = FeeModel::Tiered {
+            tiers: vec![
+                FeeTier { min_reward: Uint128::zero(), fee: Uint128::new(1) },
+                FeeTier { min_reward: Uint128::zero(), fee: Uint128::new(5) },
+            ],
+        };
+        assert!(unsorted.validate().is_err());
+        assert!(FeeModel::Percentage { bps: 10_001, min: Uint128::zero(), max: None }
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_simulate_matches_percentage_fee() {
+        let (mut app, contract_addr, owner) = setup_contract_for_user("creator");
+
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            fee_model: Some(FeeModel::Percentage {
+                bps: 500,
+                min: Uint128::new(1),
+                max: None,
+            }),
+            ..Default::default()
+        });
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let simulation: SimulateCreateQuestResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::SimulateCreateQuest {
+                    reward_amount: "100".to_string(),
+                    max_completions: Some(2),
+                    reward_asset: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(simulation.fee, Uint128::new(10));
+        assert_eq!(simulation.escrow, Uint128::new(200));
+        assert_eq!(simulation.total_cost, Uint128::new(210));
+
+        let msg = ExecuteMsg::CreateQuest(CreateQuestMsg {
+            name: "Team quest".to_string(),
+            description: "Two slots".to_string(),
+            reward_amount: "100".to_string(),
+            max_completions: Some(2),
+            ..Default::default()
+        });
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let balance: GetBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetBalance { address: owner.to_string() },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(1000) - simulation.total_cost);
+
+        let config: GetConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(config.treasury_balance, simulation.fee);
+
+        // A coin-funded quest can't be priced in internal tokens, so it pays the base fee.
+        let simulation: SimulateCreateQuestResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::SimulateCreateQuest {
+                    reward_amount: "500".to_string(),
+                    max_completions: None,
+                    reward_asset: Some(Asset::Native { denom: "uatom".to_string() }),
+                },
+            )
+            .unwrap();
+        assert_eq!(simulation.fee, Uint128::new(1));
+        assert_eq!(simulation.total_cost, Uint128::new(1));
+
+        app.execute_contract(
+            owner.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::Withdraw { amount: "500".to_string() },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(owner.clone(), contract_addr.clone(), &create_quest_msg("500"), &coins(500, "uatom"))
+            .unwrap();
+
+        let config: GetConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(config.treasury_balance, Uint128::new(11));
+    }
+
+    #[test]
+    fn test_quests_by_tag() {
+        let (mut app, contract_addr, owner) = setup_contract_for_user("creator");
+
+        for tag in ["social", "eco"] {
+            let msg = ExecuteMsg::AddCategory { tag: tag.to_string() };
+            app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        let result = app.execute_contract(
+            Addr::unchecked("stranger"),
+            contract_addr.clone(),
+            &ExecuteMsg::AddCategory { tag: "spam".to_string() },
+            &[],
+        );
+        assert_eq!(contract_error(result), ContractError::Unauthorized {}, "Only owner curates categories");
+
+        let tagged_quest = |tags: &[&str]| {
+            ExecuteMsg::CreateQuest(CreateQuestMsg {
+                name: "Tagged".to_string(),
+                description: "Quest with tags".to_string(),
+                reward_amount: "10".to_string(),
+                tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                ..Default::default()
+            })
+        };
+
+        let result = app.execute_contract(owner.clone(), contract_addr.clone(), &tagged_quest(&["gaming"]), &[]);
+        assert_eq!(
+            contract_error(result),
+            ContractError::CategoryNotFound { tag: "gaming".to_string() },
+            "Tags must come from the registry"
+        );
+
+        app.execute_contract(owner.clone(), contract_addr.clone(), &tagged_quest(&["social", "eco"]), &[])
+            .unwrap();
+        app.execute_contract(owner.clone(), contract_addr.clone(), &tagged_quest(&["eco"]), &[])
+            .unwrap();
+        app.execute_contract(owner.clone(), contract_addr.clone(), &tagged_quest(&["social"]), &[])
+            .unwrap();
+
+        let eco: QuestsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::QuestsByTag { tag: "eco".to_string(), start_after: None, limit: None },
+            )
+            .unwrap();
+        assert_eq!(eco.quests.iter().map(|q| q.id).collect::<Vec<_>>(), vec![1, 2]);
+
+        let social: QuestsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::QuestsByTag { tag: "social".to_string(), start_after: Some(1), limit: Some(1) },
+            )
+            .unwrap();
+        assert_eq!(social.quests.len(), 1);
+        assert_eq!(social.quests[0].id, 3);
+        assert_eq!(social.quests[0].tags, vec!["social".to_string()]);
+
+        app.execute_contract(
+            owner.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::RetireCategory { tag: "eco".to_string() },
+            &[],
+        )
+        .unwrap();
+
+        let result = app.execute_contract(owner.clone(), contract_addr.clone(), &tagged_quest(&["eco"]), &[]);
+        assert_eq!(
+            contract_error(result),
+            ContractError::CategoryRetired { tag: "eco".to_string() },
+            "Retired categories cannot be used on new quests"
+        );
+
+        let categories: CategoriesResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetCategories {})
+            .unwrap();
+        assert_eq!(
+            categories.categories.iter().map(|c| (c.tag.as_str(), c.active)).collect::<Vec<_>>(),
+            vec![("eco", false), ("social", true)]
+        );
+    }
+
+    #[test]
+    fn test_reputation_gates_submissions() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+
+        app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg("10"), &[])
+            .unwrap();
+        let msg = ExecuteMsg::CreateQuest(CreateQuestMsg {
+            name: "Veterans only".to_string(),
+            description: "Needs reputation".to_string(),
+            reward_amount: "50".to_string(),
+            min_reputation: Some(10),
+            ..Default::default()
+        });
+        app.execute_contract(creator.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let submit = |quest_id: u64| ExecuteMsg::SubmitCompletion {
+            quest_id,
+            proof: "ipfs://proof".to_string(),
+        };
+        let reputation = |app: &App, address: &Addr| -> Reputation {
+            app.wrap()
+                .query_wasm_smart(&contract_addr, &QueryMsg::GetReputation { address: address.to_string() })
+                .unwrap()
+        };
+
+        let result = app.execute_contract(alice.clone(), contract_addr.clone(), &submit(2), &[]);
+        assert_eq!(
+            contract_error(result),
+            ContractError::InsufficientReputation { required: 10, actual: 0 },
+            "New accounts cannot take gated quests"
+        );
+
+        app.execute_contract(alice.clone(), contract_addr.clone(), &submit(1), &[])
+            .unwrap();
+        app.execute_contract(bob.clone(), contract_addr.clone(), &submit(1), &[])
+            .unwrap();
+        app.execute_contract(
+            creator.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::RejectSubmission {
+                quest_id: 1,
+                submitter: bob.to_string(),
+                reason: "No proof".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            creator.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::ApproveSubmission { quest_id: 1, submitter: alice.to_string() },
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(
+            reputation(&app, &alice),
+            Reputation { score: 10, approved: 1, ..Default::default() }
+        );
+        assert_eq!(
+            reputation(&app, &bob),
+            Reputation { score: -5, rejected: 1, ..Default::default() }
+        );
+        assert_eq!(
+            reputation(&app, &creator),
+            Reputation { score: 5, prompt_payouts: 1, ..Default::default() }
+        );
+
+        app.execute_contract(alice, contract_addr.clone(), &submit(2), &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_leaderboard_ranks_by_metric() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+
+        let completions = [("alice", "100"), ("bob", "50"), ("alice", "30")];
+        for (quest_id, (completer, reward)) in (1u64..).zip(completions) {
+            app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg(reward), &[])
+                .unwrap();
+            app.execute_contract(
+                Addr::unchecked(completer),
+                contract_addr.clone(),
+                &ExecuteMsg::SubmitCompletion { quest_id, proof: "ipfs://proof".to_string() },
+                &[],
+            )
+            .unwrap();
+            app.execute_contract(
+                creator.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::ApproveSubmission { quest_id, submitter: completer.to_string() },
+                &[],
+            )
+            .unwrap();
+        }
+
+        let leaderboard = |app: &App, metric: LeaderboardMetric, start_after: Option<(Uint128, Addr)>| {
+            let response: LeaderboardResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    &contract_addr,
+                    &QueryMsg::Leaderboard {
+                        metric,
+                        start_after: start_after.map(|(score, address)| (score, address.to_string())),
+                        limit: Some(2),
+                    },
+                )
+                .unwrap();
+            let entries = response
+                .entries
+                .into_iter()
+                .map(|entry| (entry.address.to_string(), entry.total_earned.u128(), entry.quests_completed))
+                .collect::<Vec<_>>();
+            (entries, response.next_key)
+        };
+
+        let (entries, next_key) = leaderboard(&app, LeaderboardMetric::TotalEarned, None);
+        assert_eq!(entries, vec![("alice".to_string(), 130, 2), ("bob".to_string(), 50, 1)]);
+        assert_eq!(next_key, Some((Uint128::new(50), Addr::unchecked("bob"))));
+
+        // Bob climbing above the cursor must not make the next page repeat or skip anyone.
+        app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg("200"), &[])
+            .unwrap();
+        app.execute_contract(
+            Addr::unchecked("bob"),
+            contract_addr.clone(),
+            &ExecuteMsg::SubmitCompletion { quest_id: 4, proof: "ipfs://proof".to_string() },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            creator.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::ApproveSubmission { quest_id: 4, submitter: "bob".to_string() },
+            &[],
+        )
+        .unwrap();
+
+        let (entries, next_key) = leaderboard(&app, LeaderboardMetric::TotalEarned, next_key);
+        assert_eq!(entries, vec![("creator".to_string(), 0, 0)]);
+        assert_eq!(next_key, None);
+
+        let (entries, next_key) = leaderboard(&app, LeaderboardMetric::QuestsCompleted, None);
+        assert_eq!(entries, vec![("bob".to_string(), 250, 2), ("alice".to_string(), 130, 2)]);
+        assert_eq!(next_key, Some((Uint128::new(2), Addr::unchecked("alice"))));
+    }
+
+    #[test]
+    fn test_referral_bonus_paid_from_treasury() {
+        let (mut app, contract_addr, owner) = setup_contract_for_user("creator");
+
+        let msg = ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+            referral_bonus_bps: Some(500),
+            ..Default::default()
+        });
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let register = |referrer: &str| ExecuteMsg::RegisterReferrer { referrer: referrer.to_string() };
+        let alice = Addr::unchecked("alice");
+
+        let result = app.execute_contract(alice.clone(), contract_addr.clone(), &register("alice"), &[]);
+        assert_eq!(contract_error(result), ContractError::SelfReferral {});
+
+        app.execute_contract(alice.clone(), contract_addr.clone(), &register("scout"), &[])
+            .unwrap();
+
+        let result = app.execute_contract(alice.clone(), contract_addr.clone(), &register("creator"), &[]);
+        assert_eq!(contract_error(result), ContractError::ReferrerAlreadySet {}, "Referrer is set once");
+
+        let result = app.execute_contract(Addr::unchecked("scout"), contract_addr.clone(), &register("alice"), &[]);
+        assert_eq!(contract_error(result), ContractError::ReferralCycle {});
+
+        for (quest_id, completer) in [(1u64, "alice"), (2, "bob")] {
+            app.execute_contract(owner.clone(), contract_addr.clone(), &create_quest_msg("100"), &[])
+                .unwrap();
+            app.execute_contract(
+                Addr::unchecked(completer),
+                contract_addr.clone(),
+                &ExecuteMsg::SubmitCompletion { quest_id, proof: "ipfs://proof".to_string() },
+                &[],
+            )
+            .unwrap();
+            app.execute_contract(
+                owner.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::ApproveSubmission { quest_id, submitter: completer.to_string() },
+                &[],
+            )
+            .unwrap();
+        }
+
+        let result = app.execute_contract(Addr::unchecked("bob"), contract_addr.clone(), &register("scout"), &[]);
+        assert_eq!(contract_error(result), ContractError::NotNewUser {});
+
+        let balance = |app: &App, address: &str| {
+            let response: GetBalanceResponse = app
+                .wrap()
+                .query_wasm_smart(&contract_addr, &QueryMsg::GetBalance { address: address.to_string() })
+                .unwrap();
+            response.balance
+        };
+        assert_eq!(balance(&app, "alice"), Uint128::new(100), "Completer keeps the full reward");
+        assert_eq!(balance(&app, "scout"), Uint128::new(5));
+
+        let referrals: ReferralsResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetReferrals { address: "scout".to_string() })
+            .unwrap();
+        assert_eq!(referrals.referrer, None);
+        assert_eq!(referrals.referrals, 1);
+        assert_eq!(referrals.total_earned, Uint128::new(5));
+
+        let referrals: ReferralsResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetReferrals { address: "alice".to_string() })
+            .unwrap();
+        assert_eq!(referrals.referrer, Some(Addr::unchecked("scout")));
+
+        let config: GetConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(config.referral_bonus_bps, 500);
+        assert_eq!(config.treasury_balance, Uint128::new(5), "Bonus is drawn from accrued fees");
+    }
+
+    #[test]
+    fn test_fees_accrue_to_treasury() {
+        let (mut app, contract_addr, owner) = setup_contract_for_user("creator");
+
+        app.execute_contract(owner.clone(), contract_addr.clone(), &create_quest_msg("100"), &[])
+            .unwrap();
+        app.execute_contract(owner.clone(), contract_addr.clone(), &create_quest_msg("50"), &[])
+            .unwrap();
+
+        let config: GetConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(config.total_fees_collected, Uint128::new(10));
+        assert_eq!(config.treasury_balance, Uint128::new(10));
+
+        let msg = ExecuteMsg::AdminWithdraw {
+            amount: "11".to_string(),
+            recipient: None,
+        };
+        let result = app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[]);
+        assert_eq!(
+            contract_error(result),
+            ContractError::InsufficientBalance {
+                required: Uint128::new(11),
+                available: Uint128::new(10),
+            },
+            "Cannot withdraw more than the treasury holds"
+        );
+
+        let msg = ExecuteMsg::AdminWithdraw {
+            amount: "7".to_string(),
+            recipient: Some("ops".to_string()),
+        };
+        let result = app.execute_contract(Addr::unchecked("ops"), contract_addr.clone(), &msg, &[]);
+        assert_eq!(contract_error(result), ContractError::Unauthorized {}, "Only owner may withdraw fees");
+
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let balance: GetBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetBalance { address: "ops".to_string() })
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(7));
+
+        let config: GetConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(config.total_fees_collected, Uint128::new(10));
+        assert_eq!(config.treasury_balance, Uint128::new(3));
+
+        let token_info: TokenInfoResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::TokenInfo {})
+            .unwrap();
+        assert_eq!(token_info.total_supply, Uint128::new(1000), "Fees stay in supply");
+    }
+
+    #[test]
+    fn test_approved_submission_pays_from_escrow() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+        let completer = Addr::unchecked("completer");
+
+        app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg("100"), &[])
+            .unwrap();
+
+        let msg = ExecuteMsg::SubmitCompletion {
+            quest_id: 1,
+            proof: "ipfs://proof".to_string(),
+        };
+        app.execute_contract(completer.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let msg = ExecuteMsg::ApproveSubmission {
+            quest_id: 1,
+            submitter: completer.to_string(),
+        };
+        let result = app.execute_contract(completer.clone(), contract_addr.clone(), &msg, &[]);
+        assert_eq!(contract_error(result), ContractError::Unauthorized {}, "Only creator may approve");
+
+        app.execute_contract(creator.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let creator_balance: GetBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetBalance { address: creator.to_string() },
+            )
+            .unwrap();
+        let completer_balance: GetBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetBalance { address: completer.to_string() },
+            )
+            .unwrap();
+
+        assert_eq!(creator_balance.balance, Uint128::new(895));
+        assert_eq!(completer_balance.balance, Uint128::new(100));
+        assert_eq!(completer_balance.total_earned, Uint128::new(100));
+
+        let quest: GetQuestResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetQuest { quest_id: 1 })
+            .unwrap();
+        assert_eq!(quest.quest.status, QuestStatus::Completed);
+        assert_eq!(quest.quest.completed_by, Some(completer));
+    }
+
+    #[test]
+    fn test_rejected_submission_reopens_quest() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+        let completer = Addr::unchecked("completer");
+
+        app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg("100"), &[])
+            .unwrap();
+
+        let msg = ExecuteMsg::SubmitCompletion {
+            quest_id: 1,
+            proof: "ipfs://proof".to_string(),
+        };
+        app.execute_contract(completer.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let pending: GetSubmissionsResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetPendingSubmissions { quest_id: 1 })
+            .unwrap();
+        assert_eq!(pending.submissions.len(), 1);
+        assert_eq!(pending.submissions[0].submitter, completer);
+
+        let msg = ExecuteMsg::RejectSubmission {
+            quest_id: 1,
+            submitter: completer.to_string(),
+            reason: "Proof is unreadable".to_string(),
+        };
+        app.execute_contract(creator.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let pending: GetSubmissionsResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetPendingSubmissions { quest_id: 1 })
+            .unwrap();
+        assert!(pending.submissions.is_empty());
+
+        let quest: GetQuestResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetQuest { quest_id: 1 })
+            .unwrap();
+        assert_eq!(quest.quest.status, QuestStatus::Open);
+
+        let balance: GetBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetBalance { address: completer.to_string() },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::zero());
+    }
+
+    #[test]
+    fn test_cancel_quest_refunds_escrow() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+        let stranger = Addr::unchecked("stranger");
+
+        app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg("100"), &[])
+            .unwrap();
+
+        let msg = ExecuteMsg::CancelQuest { quest_id: 1 };
+        let result = app.execute_contract(stranger, contract_addr.clone(), &msg, &[]);
+        assert_eq!(contract_error(result), ContractError::Unauthorized {}, "Only creator or owner may cancel");
+
+        let res = app
+            .execute_contract(creator.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        assert!(res.has_event(&Event::new("wasm-quest_cancelled").add_attribute("quest_id", "1")));
+
+        let balance: GetBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetBalance { address: creator.to_string() },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(995));
+
+        let active: GetActiveQuestsResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetActiveQuests { start_after: None, limit: None })
+            .unwrap();
+        assert_eq!(active.count, 0);
+
+        let result = app.execute_contract(
+            Addr::unchecked("completer"),
+            contract_addr,
+            &ExecuteMsg::SubmitCompletion {
+                quest_id: 1,
+                proof: "ipfs://proof".to_string(),
+            },
+            &[],
+        );
+        assert_eq!(contract_error(result), ContractError::QuestNotActive {}, "Cancelled quest cannot be completed");
+    }
+
+    #[test]
+    fn test_expire_overdue_quests() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+        let deadline = app.block_info().time.seconds() + 100;
+
+        let msg = ExecuteMsg::CreateQuest(CreateQuestMsg {
+            name: "Timed Quest".to_string(),
+            description: "Must be done quickly".to_string(),
+            reward_amount: "100".to_string(),
+            deadline: Some(deadline),
+            ..Default::default()
+        });
+        app.execute_contract(creator.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg("50"), &[])
+            .unwrap();
+        let msg = ExecuteMsg::CreateQuest(CreateQuestMsg {
+            name: "Later Quest".to_string(),
+            description: "Still has time".to_string(),
+            reward_amount: "10".to_string(),
+            deadline: Some(deadline + 1000),
+            ..Default::default()
+        });
+        app.execute_contract(creator.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        app.update_block(|block| block.time = block.time.plus_seconds(101));
+
+        let result = app.execute_contract(
+            Addr::unchecked("completer"),
+            contract_addr.clone(),
+            &ExecuteMsg::SubmitCompletion {
+                quest_id: 1,
+                proof: "ipfs://proof".to_string(),
+            },
+            &[],
+        );
+        assert_eq!(contract_error(result), ContractError::QuestExpired {}, "Overdue quest cannot be completed");
+
+        let active: GetActiveQuestsResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetActiveQuests { start_after: None, limit: None })
+            .unwrap();
+        assert_eq!(active.count, 2);
+        assert_eq!(active.quests[0].id, 2);
+
+        let res = app
+            .execute_contract(
+                Addr::unchecked("anyone"),
+                contract_addr.clone(),
+                &ExecuteMsg::ExpireQuests { limit: None },
+                &[],
+            )
+            .unwrap();
+        let expired = res.events.iter().filter(|e| e.ty == "wasm-quest_expired").count();
+        assert_eq!(expired, 1, "Only overdue quests are visited");
+
+        let status = |app: &App, quest_id: u64| {
+            let response: GetQuestResponse = app
+                .wrap()
+                .query_wasm_smart(&contract_addr, &QueryMsg::GetQuest { quest_id })
+                .unwrap();
+            response.quest.status
+        };
+        assert_eq!(status(&app, 1), QuestStatus::Expired);
+        assert_eq!(status(&app, 3), QuestStatus::Open);
+
+        let balance: GetBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetBalance { address: creator.to_string() },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(925));
+    }
+
+    #[test]
+    fn test_finalize_submission_after_review_period() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+        let completer = Addr::unchecked("completer");
+
+        app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg("100"), &[])
+            .unwrap();
+
+        let msg = ExecuteMsg::SubmitCompletion {
+            quest_id: 1,
+            proof: "ipfs://proof".to_string(),
+        };
+        app.execute_contract(completer.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let msg = ExecuteMsg::FinalizeSubmission {
+            quest_id: 1,
+            submitter: completer.to_string(),
+        };
+        let result = app.execute_contract(Addr::unchecked("anyone"), contract_addr.clone(), &msg, &[]);
+        assert!(
+            matches!(contract_error(result), ContractError::ReviewPeriodActive { .. }),
+            "Review period has not ended yet"
+        );
+
+        app.update_block(|block| block.time = block.time.plus_seconds(100));
+        app.execute_contract(Addr::unchecked("anyone"), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let balance: GetBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetBalance { address: completer.to_string() },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(100));
+    }
+
+    #[test]
+    fn test_multi_slot_quest_pays_distinct_completers() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+
+        let msg = ExecuteMsg::CreateQuest(CreateQuestMsg {
+            name: "First two".to_string(),
+            description: "Paid to the first two completers".to_string(),
+            reward_amount: "100".to_string(),
+            max_completions: Some(2),
+            ..Default::default()
+        });
+        app.execute_contract(creator.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let balance: GetBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetBalance { address: creator.to_string() },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(795));
+
+        for name in ["alice", "bob"] {
+            let submitter = Addr::unchecked(name);
+            let msg = ExecuteMsg::SubmitCompletion {
+                quest_id: 1,
+                proof: "ipfs://proof".to_string(),
+            };
+            app.execute_contract(submitter.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+
+            let msg = ExecuteMsg::ApproveSubmission {
+                quest_id: 1,
+                submitter: submitter.to_string(),
+            };
+            app.execute_contract(creator.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+
+            let result = app.execute_contract(
+                submitter,
+                contract_addr.clone(),
+                &ExecuteMsg::SubmitCompletion {
+                    quest_id: 1,
+                    proof: "ipfs://again".to_string(),
+                },
+                &[],
+            );
+            assert!(result.is_err(), "Same address must not complete twice");
+
+            if name == "alice" {
+                let quest: GetQuestResponse = app
+                    .wrap()
+                    .query_wasm_smart(&contract_addr, &QueryMsg::GetQuest { quest_id: 1 })
+                    .unwrap();
+                assert_eq!(quest.remaining_slots, 1);
+                assert_eq!(quest.quest.status, QuestStatus::Open);
+            }
+        }
+
+        let quest: GetQuestResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetQuest { quest_id: 1 })
+            .unwrap();
+        assert_eq!(quest.remaining_slots, 0);
+        assert_eq!(quest.quest.status, QuestStatus::Completed);
+    }
+
+    #[test]
+    fn test_completion_closes_leftover_submissions() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+
+        app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg("100"), &[])
+            .unwrap();
+        for name in ["alice", "bob"] {
+            let msg = ExecuteMsg::SubmitCompletion {
+                quest_id: 1,
+                proof: "ipfs://proof".to_string(),
+            };
+            app.execute_contract(Addr::unchecked(name), contract_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        let msg = ExecuteMsg::ApproveSubmission {
+            quest_id: 1,
+            submitter: "alice".to_string(),
+        };
+        app.execute_contract(creator.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let pending: GetSubmissionsResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetPendingSubmissions { quest_id: 1 })
+            .unwrap();
+        assert!(pending.submissions.is_empty(), "Filling the last slot closes other submissions");
+
+        let msg = ExecuteMsg::RejectSubmission {
+            quest_id: 1,
+            submitter: "bob".to_string(),
+            reason: "too late".to_string(),
+        };
+        let result = app.execute_contract(creator.clone(), contract_addr.clone(), &msg, &[]);
+        assert_eq!(contract_error(result), ContractError::SubmissionAlreadyReviewed {});
+
+        let reputation: Reputation = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetReputation { address: "bob".to_string() })
+            .unwrap();
+        assert_eq!(reputation, Reputation::default(), "Closing is not a rejection");
+    }
+
+    #[test]
+    fn test_arbiter_resolves_dispute_for_submitter() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+        let completer = Addr::unchecked("completer");
+        let arbiter = Addr::unchecked("arbiter");
+
+        app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg("100"), &[])
+            .unwrap();
+
+        let msg = ExecuteMsg::SubmitCompletion {
+            quest_id: 1,
+            proof: "ipfs://proof".to_string(),
+        };
+        app.execute_contract(completer.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let msg = ExecuteMsg::RejectSubmission {
+            quest_id: 1,
+            submitter: completer.to_string(),
+            reason: "Not good enough".to_string(),
+        };
+        app.execute_contract(creator.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let msg = ExecuteMsg::Dispute {
+            quest_id: 1,
+            reason: "The work was done".to_string(),
+        };
+        app.execute_contract(completer.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let result = app.execute_contract(
+            creator.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::CancelQuest { quest_id: 1 },
+            &[],
+        );
+        assert_eq!(contract_error(result), ContractError::QuestNotActive {}, "Escrow is frozen while disputed");
+
+        let quest: GetQuestResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetQuest { quest_id: 1 })
+            .unwrap();
+        assert_eq!(quest.quest.status, QuestStatus::Disputed);
+        assert_eq!(quest.remaining_slots, 1);
+
+        let open: GetDisputesResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetDisputes {
+                    status: Some(DisputeStatus::Open),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(open.disputes.len(), 1);
+
+        let msg = ExecuteMsg::ResolveDispute {
+            quest_id: 1,
+            submitter: completer.to_string(),
+            winner: DisputeParty::Submitter,
+        };
+        let result = app.execute_contract(creator, contract_addr.clone(), &msg, &[]);
+        assert_eq!(contract_error(result), ContractError::Unauthorized {}, "Only arbiter may resolve");
+
+        app.execute_contract(arbiter, contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let balance: GetBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetBalance { address: completer.to_string() },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(100));
+
+        let resolved: GetDisputesResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetDisputes {
+                    status: Some(DisputeStatus::Resolved),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(resolved.disputes[0].winner, Some(DisputeParty::Submitter));
+    }
+
+    #[test]
+    fn test_rejection_keeps_escrow_until_dispute_window_closes() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+        let completer = Addr::unchecked("completer");
+        let deadline = app.block_info().time.seconds() + 50;
+
+        app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg("100"), &[])
+            .unwrap();
+        let msg = ExecuteMsg::CreateQuest(CreateQuestMsg {
+            name: "Timed Quest".to_string(),
+            description: "Must be done quickly".to_string(),
+            reward_amount: "100".to_string(),
+            deadline: Some(deadline),
+            ..Default::default()
+        });
+        app.execute_contract(creator.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        for quest_id in [1, 2] {
+            let msg = ExecuteMsg::SubmitCompletion {
+                quest_id,
+                proof: "ipfs://proof".to_string(),
+            };
+            app.execute_contract(completer.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+
+            let msg = ExecuteMsg::RejectSubmission {
+                quest_id,
+                submitter: completer.to_string(),
+                reason: "Not good enough".to_string(),
+            };
+            app.execute_contract(creator.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+        let rejected_at = app.block_info().time.seconds();
+
+        let result = app.execute_contract(
+            creator.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::CancelQuest { quest_id: 1 },
+            &[],
+        );
+        assert_eq!(
+            contract_error(result),
+            ContractError::DisputeWindowOpen { ends_at: rejected_at + 100 },
+            "Rejection must not open an escape hatch for the escrow"
+        );
+
+        let msg = ExecuteMsg::Dispute {
+            quest_id: 1,
+            reason: "The work was done".to_string(),
+        };
+        app.execute_contract(completer.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        app.update_block(|block| block.time = block.time.plus_seconds(60));
+        let expire = ExecuteMsg::ExpireQuests { limit: None };
+        let res = app
+            .execute_contract(Addr::unchecked("anyone"), contract_addr.clone(), &expire, &[])
+            .unwrap();
+        assert!(
+            !res.events.iter().any(|e| e.ty == "wasm-quest_expired"),
+            "Crank skips quests whose rejections can still be disputed"
+        );
+
+        app.update_block(|block| block.time = block.time.plus_seconds(40));
+        let msg = ExecuteMsg::Dispute {
+            quest_id: 2,
+            reason: "The work was done".to_string(),
+        };
+        let result = app.execute_contract(completer, contract_addr.clone(), &msg, &[]);
+        assert_eq!(contract_error(result), ContractError::DisputeWindowClosed {});
+
+        let res = app
+            .execute_contract(Addr::unchecked("anyone"), contract_addr.clone(), &expire, &[])
+            .unwrap();
+        assert!(res.has_event(&Event::new("wasm-quest_expired").add_attribute("quest_id", "2")));
+    }
+
+    #[test]
+    fn test_dispute_does_not_run_down_other_review_periods() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+
+        app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg("100"), &[])
+            .unwrap();
+        for submitter in [&alice, &bob] {
+            let msg = ExecuteMsg::SubmitCompletion {
+                quest_id: 1,
+                proof: "ipfs://proof".to_string(),
+            };
+            app.execute_contract(submitter.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        let msg = ExecuteMsg::RejectSubmission {
+            quest_id: 1,
+            submitter: alice.to_string(),
+            reason: "Not good enough".to_string(),
+        };
+        app.execute_contract(creator.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let msg = ExecuteMsg::Dispute {
+            quest_id: 1,
+            reason: "The work was done".to_string(),
+        };
+        app.execute_contract(alice.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        app.update_block(|block| block.time = block.time.plus_seconds(200));
+        let msg = ExecuteMsg::ResolveDispute {
+            quest_id: 1,
+            submitter: alice.to_string(),
+            winner: DisputeParty::Creator,
+        };
+        app.execute_contract(Addr::unchecked("arbiter"), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let msg = ExecuteMsg::FinalizeSubmission {
+            quest_id: 1,
+            submitter: bob.to_string(),
+        };
+        let result = app.execute_contract(Addr::unchecked("anyone"), contract_addr.clone(), &msg, &[]);
+        assert!(
+            matches!(contract_error(result), ContractError::ReviewPeriodActive { .. }),
+            "Time spent disputed does not count against the creator"
+        );
+
+        let msg = ExecuteMsg::RejectSubmission {
+            quest_id: 1,
+            submitter: bob.to_string(),
+            reason: "Not good enough either".to_string(),
+        };
+        app.execute_contract(creator, contract_addr, &msg, &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_disputes_paginate_by_status() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+        let alice = Addr::unchecked("alice");
+
+        for quest_id in 1..=3u64 {
+            app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg("50"), &[])
+                .unwrap();
+            app.execute_contract(
+                alice.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::SubmitCompletion { quest_id, proof: "ipfs://proof".to_string() },
+                &[],
+            )
+            .unwrap();
+            app.execute_contract(
+                creator.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::RejectSubmission {
+                    quest_id,
+                    submitter: alice.to_string(),
+                    reason: "incomplete".to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+            app.execute_contract(
+                alice.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::Dispute { quest_id, reason: "it was done".to_string() },
+                &[],
+            )
+            .unwrap();
+        }
+        app.execute_contract(
+            Addr::unchecked("arbiter"),
+            contract_addr.clone(),
+            &ExecuteMsg::ResolveDispute {
+                quest_id: 2,
+                submitter: alice.to_string(),
+                winner: DisputeParty::Creator,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let disputes = |status: Option<DisputeStatus>, start_after: Option<u64>| {
+            let response: GetDisputesResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    &contract_addr,
+                    &QueryMsg::GetDisputes {
+                        status,
+                        start_after: start_after.map(|quest_id| (quest_id, "alice".to_string())),
+                        limit: Some(1),
+                    },
+                )
+                .unwrap();
+            let ids: Vec<u64> = response.disputes.iter().map(|dispute| dispute.quest_id).collect();
+            (ids, response.next_key.map(|(quest_id, _)| quest_id))
+        };
+
+        assert_eq!(disputes(Some(DisputeStatus::Open), None), (vec![1], Some(1)));
+        assert_eq!(disputes(Some(DisputeStatus::Open), Some(1)), (vec![3], Some(3)));
+        assert_eq!(disputes(Some(DisputeStatus::Open), Some(3)), (vec![], None));
+        assert_eq!(disputes(Some(DisputeStatus::Resolved), None), (vec![2], Some(2)));
+        assert_eq!(disputes(None, Some(1)), (vec![2], Some(2)));
+    }
+
+    #[test]
+    fn test_deposit_and_withdraw_native_coins() {
+        let user = Addr::unchecked("user");
+        let mut app = App::new(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &user, coins(500, "uatom"))
+                .unwrap();
+        });
+        let code_id = app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query)));
+
+        let msg = InstantiateMsg {
+            owner: None,
+            arbiter: None,
+            treasury: None,
+            badge_nft_contract: None,
+            denom: "uatom".to_string(),
+            quest_creation_fee: Some("5".to_string()),
+            fee_model: None,
+            initial_balance: "1000".to_string(),
+            review_period: None,
+            max_name_length: None,
+            max_description_length: None,
+            referral_bonus_bps: None,
+            token_name: None,
+            token_symbol: None,
+            token_decimals: None,
+        };
+        let result =
+            app.instantiate_contract(code_id, Addr::unchecked("admin"), &msg, &[], "quest-contract", None);
+        assert_eq!(
+            contract_error(result),
+            ContractError::Payment(PaymentError::NoFunds {}),
+            "Seeded balance must be paid in coins"
+        );
+
+        let both = InstantiateMsg {
+            fee_model: Some(FeeModel::Flat { amount: Uint128::new(5) }),
+            ..msg.clone()
+        };
+        let result =
+            app.instantiate_contract(code_id, Addr::unchecked("admin"), &both, &[], "quest-contract", None);
+        assert!(
+            matches!(contract_error(result), ContractError::InvalidInput { .. }),
+            "quest_creation_fee and fee_model are mutually exclusive"
+        );
+
+        let msg = InstantiateMsg { initial_balance: "0".to_string(), ..msg };
+        let contract_addr = app
+            .instantiate_contract(code_id, Addr::unchecked("admin"), &msg, &[], "quest-contract", None)
+            .unwrap();
+
+        let result = app.execute_contract(
+            user.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::Deposit {},
+            &[],
+        );
+        assert_eq!(
+            contract_error(result),
+            ContractError::Payment(PaymentError::NoFunds {}),
+            "Deposit requires attached funds"
+        );
+
+        app.execute_contract(
+            user.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::Deposit {},
+            &coins(300, "uatom"),
+        )
+        .unwrap();
+
+        let balance: GetBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetBalance { address: user.to_string() },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(300));
+
+        app.execute_contract(
+            user.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::Withdraw { amount: "120".to_string() },
+            &[],
+        )
+        .unwrap();
+
+        let bank_balance = app.wrap().query_balance(&user, "uatom").unwrap();
+        assert_eq!(bank_balance.amount, Uint128::new(320));
+
+        let contract_balance = app.wrap().query_balance(&contract_addr, "uatom").unwrap();
+        assert_eq!(contract_balance.amount, Uint128::new(180));
+
+        let result = app.execute_contract(
+            user.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::Withdraw { amount: "10".to_string() },
+            &coins(10, "uatom"),
+        );
+        assert_eq!(
+            contract_error(result),
+            ContractError::Payment(PaymentError::NonPayable {}),
+            "Only paying messages accept funds"
+        );
+
+        let result = app.execute_contract(
+            user,
+            contract_addr,
+            &ExecuteMsg::Withdraw { amount: "181".to_string() },
+            &[],
+        );
+        assert_eq!(
+            contract_error(result),
+            ContractError::InsufficientBalance {
+                required: Uint128::new(181),
+                available: Uint128::new(180),
+            },
+            "Cannot withdraw more than the ledger balance"
+        );
+    }
+
+    #[test]
+    fn test_cw20_queries_and_burn() {
+        let (mut app, contract_addr, user) = setup_contract_for_user("creator");
+
+        app.execute_contract(
+            user.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: "alice".to_string(),
+                amount: "250".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            user.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::Burn { amount: "50".to_string() },
+            &[],
+        )
+        .unwrap();
+
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::Balance { address: user.to_string() })
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(700));
+
+        let info: TokenInfoResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::TokenInfo {})
+            .unwrap();
+        assert_eq!(info.symbol, "QUEST");
+        assert_eq!(info.total_supply, Uint128::new(950));
 
-    if quest.completed {
-        return Err(StdError::generic_err("Квест уже выполнен"));
+        let accounts: AllAccountsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::AllAccounts { start_after: None, limit: None },
+            )
+            .unwrap();
+        assert_eq!(accounts.accounts, vec!["alice".to_string(), "creator".to_string()]);
     }
 
-    if quest.creator == info.sender {
-        return Err(StdError::generic_err(
-            "Вы не можете выполнить свой собственный квест"
-        ));
-    }
+    #[test]
+    fn test_batch_transfer() {
+        let (mut app, contract_addr, sender) = setup_contract_for_user("creator");
 
-    let mut completer_balance = get_or_create_balance(deps.storage, &info.sender)?;
+        let msg = ExecuteMsg::BatchTransfer {
+            transfers: vec![
+                ("alice".to_string(), "600".to_string()),
+                ("bob".to_string(), "401".to_string()),
+            ],
+        };
+        let result = app.execute_contract(sender.clone(), contract_addr.clone(), &msg, &[]);
+        assert_eq!(
+            contract_error(result),
+            ContractError::InsufficientBalance {
+                required: Uint128::new(1001),
+                available: Uint128::new(1000),
+            },
+            "Total is checked before any balance moves"
+        );
 
-    completer_balance.balance += quest.reward_amount;
-    completer_balance.total_earned += quest.reward_amount;
-    completer_balance.quests_completed += 1;
+        let msg = ExecuteMsg::BatchTransfer {
+            transfers: vec![
+                ("alice".to_string(), "100".to_string()),
+                ("bob".to_string(), "250".to_string()),
+                ("alice".to_string(), "50".to_string()),
+                (sender.to_string(), "10".to_string()),
+            ],
+        };
+        let res = app
+            .execute_contract(sender.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let summary = res.events.iter().find(|e| e.ty == "wasm-batch_transfer").unwrap();
+        assert!(summary.attributes.iter().any(|a| a.key == "total" && a.value == "410"));
 
-    let creator_balance = get_or_create_balance(deps.storage, &quest.creator)?;
+        for (address, expected) in [("alice", 150), ("bob", 250), ("creator", 600)] {
+            let balance: BalanceResponse = app
+                .wrap()
+                .query_wasm_smart(&contract_addr, &QueryMsg::Balance { address: address.to_string() })
+                .unwrap();
+            assert_eq!(balance.balance, Uint128::new(expected), "{} balance", address);
+        }
+    }
 
-    quest.completed = true;
-    quest.completed_by = Some(info.sender.clone());
-    quest.completed_at = Some(_env.block.time.seconds());
+    #[test]
+    fn test_allowance_transfer_from() {
+        let (mut app, contract_addr, owner) = setup_contract_for_user("creator");
+        let shop = Addr::unchecked("shop");
 
-    let mut config = CONFIG.load(deps.storage)?;
-    config.total_completed += 1;
+        let expires = Expiration::AtHeight(app.block_info().height + 10);
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: shop.to_string(),
+            amount: "300".to_string(),
+            expires: Some(expires),
+        };
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
 
-    QUESTS.save(deps.storage, quest_id, &quest)?;
-    BALANCES.save(deps.storage, &info.sender, &completer_balance)?;
-    BALANCES.save(deps.storage, &quest.creator, &creator_balance)?;
-    CONFIG.save(deps.storage, &config)?;
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.to_string(),
+            recipient: "merchant".to_string(),
+            amount: "200".to_string(),
+        };
+        app.execute_contract(shop.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
 
-    Ok(Response::new()
-        .add_attribute("method", "complete_quest")
-        .add_attribute("quest_id", quest_id.to_string())
-        .add_attribute("completed_by", info.sender.to_string())
-        .add_attribute("reward", quest.reward_amount.to_string())
-        .add_attribute("new_balance", completer_balance.balance.to_string())
-        .add_attribute("total_earned", completer_balance.total_earned.to_string()))
-}
+        let merchant: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::Balance { address: "merchant".to_string() })
+            .unwrap();
+        assert_eq!(merchant.balance, Uint128::new(200));
 
+        let allowance: AllowanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::Allowance { owner: owner.to_string(), spender: shop.to_string() },
+            )
+            .unwrap();
+        assert_eq!(allowance, AllowanceResponse { allowance: Uint128::new(100), expires });
 
-fn execute_transfer(
-    deps: DepsMut,
-    info: MessageInfo,
-    recipient: String,
-    amount: String,
-) -> StdResult<Response> {
-    let transfer_amount = Uint128::from_str(&amount)
-        .map_err(|_| StdError::generic_err("amount must be a valid number"))?;
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.to_string(),
+            recipient: "merchant".to_string(),
+            amount: "101".to_string(),
+        };
+        let result = app.execute_contract(shop.clone(), contract_addr.clone(), &msg, &[]);
+        assert_eq!(
+            contract_error(result),
+            ContractError::InsufficientAllowance {
+                required: Uint128::new(101),
+                available: Uint128::new(100),
+            },
+            "Spender cannot exceed the allowance"
+        );
 
-    let recipient_addr = deps.api.addr_validate(&recipient)?;
+        let msg = ExecuteMsg::DecreaseAllowance {
+            spender: shop.to_string(),
+            amount: "40".to_string(),
+            expires: None,
+        };
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
 
-    let mut sender_balance = get_or_create_balance(deps.storage, &info.sender)?;
+        app.update_block(|block| block.height += 10);
 
-    if sender_balance.balance < transfer_amount {
-        return Err(StdError::generic_err(
-            format!(
-                "Недостаточно токенов. Требуется: {}, у вас: {}",
-                transfer_amount, sender_balance.balance
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.to_string(),
+            recipient: "merchant".to_string(),
+            amount: "10".to_string(),
+        };
+        let result = app.execute_contract(shop.clone(), contract_addr.clone(), &msg, &[]);
+        assert_eq!(contract_error(result), ContractError::AllowanceExpired {}, "Expired allowance cannot be spent");
+
+        let allowance: AllowanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::Allowance { owner: owner.to_string(), spender: shop.to_string() },
             )
-        ));
-    }
+            .unwrap();
+        assert_eq!(allowance.allowance, Uint128::zero());
 
-    sender_balance.balance -= transfer_amount;
-    let mut recipient_balance = get_or_create_balance(deps.storage, &recipient_addr)?;
-    recipient_balance.balance += transfer_amount;
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: owner.to_string(),
+            amount: "1".to_string(),
+            expires: None,
+        };
+        let result = app.execute_contract(owner, contract_addr, &msg, &[]);
+        assert_eq!(contract_error(result), ContractError::CannotSetOwnAccount {});
+    }
 
-    BALANCES.save(deps.storage, &info.sender, &sender_balance)?;
-    BALANCES.save(deps.storage, &recipient_addr, &recipient_balance)?;
+    #[test]
+    fn test_cw20_send_calls_receiver() {
+        let (mut app, contract_addr, user) = setup_contract_for_user("creator");
 
-    Ok(Response::new()
-        .add_attribute("method", "transfer")
-        .add_attribute("from", info.sender.to_string())
-        .add_attribute("to", recipient)
-        .add_attribute("amount", transfer_amount.to_string())
-        .add_attribute("sender_new_balance", sender_balance.balance.to_string())
-        .add_attribute("recipient_new_balance", recipient_balance.balance.to_string()))
-}
+        let receiver = ContractWrapper::new(
+            |_, _, _, msg: ReceiverExecuteMsg| -> StdResult<Response> {
+                let ReceiverExecuteMsg::Receive(receive) = msg;
+                Ok(Response::new().add_attribute("received", receive.amount.to_string()))
+            },
+            |_, _, _, _: Empty| -> StdResult<Response> { Ok(Response::new()) },
+            |_, _, _: Empty| -> StdResult<Binary> { Ok(Binary::default()) },
+        );
+        let receiver_id = app.store_code(Box::new(receiver));
+        let receiver_addr = app
+            .instantiate_contract(receiver_id, user.clone(), &Empty {}, &[], "receiver", None)
+            .unwrap();
 
+        let res = app
+            .execute_contract(
+                user,
+                contract_addr.clone(),
+                &ExecuteMsg::Send {
+                    contract: receiver_addr.to_string(),
+                    amount: "40".to_string(),
+                    msg: Binary::default(),
+                },
+                &[],
+            )
+            .unwrap();
+        assert!(res.has_event(&Event::new("wasm").add_attribute("received", "40")));
 
-fn execute_admin_withdraw(
-    deps: DepsMut,
-    info: MessageInfo,
-    amount: String,
-) -> StdResult<Response> {
-    let withdraw_amount = Uint128::from_str(&amount)
-        .map_err(|_| StdError::generic_err("amount must be a valid number"))?;
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::Balance { address: receiver_addr.to_string() },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(40));
+    }
 
-    let config = CONFIG.load(deps.storage)?;
+    const MOCK_CW20_BALANCES: Map<&str, Uint128> = Map::new("mock_balances");
 
-    if info.sender != config.owner {
-        return Err(StdError::generic_err("Только владелец может это делать"));
+    fn mock_cw20_execute(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: Cw20ExecuteMsg,
+    ) -> StdResult<Response> {
+        let Cw20ExecuteMsg::Transfer { recipient, amount } = msg;
+        MOCK_CW20_BALANCES.update(deps.storage, &recipient, |balance| -> StdResult<_> {
+            Ok(balance.unwrap_or_default() + amount)
+        })?;
+        Ok(Response::new())
     }
 
-    let mut owner_balance = get_or_create_balance(deps.storage, &config.owner)?;
-
-    if owner_balance.balance < withdraw_amount {
-        return Err(StdError::generic_err("Недостаточно средств для вывода"));
+    fn mock_cw20_query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+        match msg {
+            QueryMsg::Balance { address } => to_json_binary(&BalanceResponse {
+                balance: MOCK_CW20_BALANCES
+                    .may_load(deps.storage, &address)?
+                    .unwrap_or_default(),
+            }),
+            _ => Err(StdError::generic_err("unsupported")),
+        }
     }
 
-    owner_balance.balance -= withdraw_amount;
-    BALANCES.save(deps.storage, &config.owner, &owner_balance)?;
+    #[test]
+    fn test_cw20_funded_quest_pays_out_in_cw20() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+        let completer = Addr::unchecked("completer");
 
-    Ok(Response::new()
-        .add_attribute("method", "admin_withdraw")
-        .add_attribute("amount", withdraw_amount.to_string())
-        .add_attribute("new_balance", owner_balance.balance.to_string()))
-}
+        let token = ContractWrapper::new(
+            mock_cw20_execute,
+            |_, _, _, _: Empty| -> StdResult<Response> { Ok(Response::new()) },
+            mock_cw20_query,
+        );
+        let token_id = app.store_code(Box::new(token));
+        let token_addr = app
+            .instantiate_contract(token_id, creator.clone(), &Empty {}, &[], "sponsor-token", None)
+            .unwrap();
 
+        let payload = ReceiveMsg::CreateQuest(CreateQuestMsg {
+            name: "Sponsored".to_string(),
+            description: "Paid in sponsor tokens".to_string(),
+            reward_amount: "70".to_string(),
+            ..Default::default()
+        });
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: creator.to_string(),
+            amount: Uint128::new(70),
+            msg: to_json_binary(&payload).unwrap(),
+        });
+        app.execute_contract(token_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
 
-// ============= QUERY HANDLERS =============
+        let quest: GetQuestResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetQuest { quest_id: 1 })
+            .unwrap();
+        assert_eq!(quest.quest.creator, creator);
+        assert_eq!(
+            quest.quest.reward_asset,
+            Asset::Cw20 { contract: token_addr.clone() }
+        );
 
+        let msg = ExecuteMsg::SubmitCompletion {
+            quest_id: 1,
+            proof: "ipfs://proof".to_string(),
+        };
+        app.execute_contract(completer.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let msg = ExecuteMsg::ApproveSubmission {
+            quest_id: 1,
+            submitter: completer.to_string(),
+        };
+        app.execute_contract(creator.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetConfig {} => to_json_binary(&query_config(deps)?),
-        QueryMsg::GetBalance { address } => to_json_binary(&query_balance(deps, address)?),
-        QueryMsg::GetQuest { quest_id } => to_json_binary(&query_quest(deps, quest_id)?),
-        QueryMsg::GetActiveQuests {} => to_json_binary(&query_active_quests(deps)?),
-        QueryMsg::GetUserQuests { address } => to_json_binary(&query_user_quests(deps, address)?),
-        QueryMsg::GetUserStats { address } => to_json_binary(&query_user_stats(deps, address)?),
+        let token_balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(&token_addr, &QueryMsg::Balance { address: completer.to_string() })
+            .unwrap();
+        assert_eq!(token_balance.balance, Uint128::new(70));
+
+        let internal: GetBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetBalance { address: creator.to_string() },
+            )
+            .unwrap();
+        assert_eq!(internal.balance, Uint128::new(995));
     }
-}
 
+    #[test]
+    fn test_paginated_and_filtered_quest_listing() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
 
-fn query_config(deps: Deps) -> StdResult<GetConfigResponse> {
-    let config = CONFIG.load(deps.storage)?;
-    Ok(GetConfigResponse {
-        owner: config.owner,
-        quest_creation_fee: config.quest_creation_fee,
-        total_quests: config.total_quests,
-        total_completed: config.total_completed,
-    })
-}
+        for reward in ["10", "50", "100"] {
+            app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg(reward), &[])
+                .unwrap();
+        }
 
+        let page: GetActiveQuestsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetActiveQuests { start_after: None, limit: Some(2) },
+            )
+            .unwrap();
+        assert_eq!(page.count, 2);
+        assert_eq!(page.next_key, Some(2));
 
-fn query_balance(deps: Deps, address: String) -> StdResult<GetBalanceResponse> {
-    let addr = deps.api.addr_validate(&address)?;
-    let balance = get_or_create_balance(deps.storage, &addr)?;
+        let page: GetActiveQuestsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetActiveQuests { start_after: page.next_key, limit: Some(2) },
+            )
+            .unwrap();
+        assert_eq!(page.quests[0].id, 3);
+        assert_eq!(page.next_key, None);
 
-    Ok(GetBalanceResponse {
-        address: addr,
-        balance: balance.balance,
-        total_earned: balance.total_earned,
-    })
-}
+        let listed: QuestsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::ListQuests {
+                    status: Some(QuestStatus::Open),
+                    creator: Some(creator.to_string()),
+                    min_reward: Some("50".to_string()),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        let ids: Vec<u64> = listed.quests.iter().map(|quest| quest.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+
+        for _ in 0..MAX_QUEST_SCAN {
+            app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg("1"), &[])
+                .unwrap();
+        }
+        let list_rich = |start_after: Option<u64>| {
+            let response: QuestsResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    &contract_addr,
+                    &QueryMsg::ListQuests {
+                        status: None,
+                        creator: None,
+                        min_reward: Some("100".to_string()),
+                        start_after,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            let ids: Vec<u64> = response.quests.iter().map(|quest| quest.id).collect();
+            (ids, response.next_key)
+        };
+        let scan_end = MAX_QUEST_SCAN as u64;
+        assert_eq!(list_rich(None), (vec![3], Some(scan_end)), "One call scans a bounded window");
+        assert_eq!(list_rich(Some(scan_end)), (vec![], None));
 
+        let user_quests: QuestsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetUserQuests {
+                    address: creator.to_string(),
+                    start_after: Some(1),
+                    limit: Some(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(user_quests.quests[0].id, 2);
+        assert_eq!(user_quests.next_key, Some(2));
+    }
 
-fn query_quest(deps: Deps, quest_id: u64) -> StdResult<GetQuestResponse> {
-    let quest = QUESTS.load(deps.storage, quest_id)
-        .map_err(|_| StdError::generic_err("Квест не найден"))?;
-    Ok(GetQuestResponse { quest })
-}
+    #[test]
+    fn test_completed_quests_by_completer() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+        let completer = Addr::unchecked("completer");
 
+        for _ in 0..3 {
+            app.execute_contract(creator.clone(), contract_addr.clone(), &create_quest_msg("10"), &[])
+                .unwrap();
+        }
 
-fn query_active_quests(deps: Deps) -> StdResult<GetActiveQuestsResponse> {
-    let quests: Vec<Quest> = QUESTS
-        .range(deps.storage, None, None, Order::Ascending)
-        .filter_map(|r| {
-            if let Ok((_, quest)) = r {
-                if !quest.completed {
-                    return Some(quest);
-                }
-            }
-            None
-        })
-        .collect();
+        for quest_id in [1, 3] {
+            let msg = ExecuteMsg::SubmitCompletion {
+                quest_id,
+                proof: "ipfs://proof".to_string(),
+            };
+            app.execute_contract(completer.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+            let msg = ExecuteMsg::ApproveSubmission {
+                quest_id,
+                submitter: completer.to_string(),
+            };
+            app.execute_contract(creator.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+        }
 
-    let count = quests.len() as u64;
-    Ok(GetActiveQuestsResponse { quests, count })
-}
+        let completed: QuestsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetCompletedQuests {
+                    address: completer.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        let ids: Vec<u64> = completed.quests.iter().map(|quest| quest.id).collect();
+        assert_eq!(ids, vec![1, 3]);
 
+        let open: QuestsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::ListQuests {
+                    status: Some(QuestStatus::Open),
+                    creator: None,
+                    min_reward: None,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(open.quests.len(), 1);
+        assert_eq!(open.quests[0].id, 2);
+    }
 
-fn query_user_quests(deps: Deps, address: String) -> StdResult<Vec<Quest>> {
-    let addr = deps.api.addr_validate(&address)?;
-    let quest_ids = USER_QUESTS
-        .may_load(deps.storage, &addr)?
-        .unwrap_or_default();
+    fn save_legacy_state(storage: &mut dyn cosmwasm_std::Storage) {
+        let creator = Addr::unchecked("creator");
+        let poor = Addr::unchecked("poor");
 
-    let mut quests = Vec::new();
-    for id in quest_ids {
-        if let Ok(quest) = QUESTS.load(deps.storage, id) {
-            quests.push(quest);
+        CONFIG_V0_1
+            .save(
+                storage,
+                &ConfigV0_1 {
+                    owner: creator.clone(),
+                    quest_creation_fee: Uint128::new(5),
+                    total_quests: 2,
+                    total_completed: 0,
+                },
+            )
+            .unwrap();
+        for (id, owner) in [(1, &creator), (2, &poor)] {
+            let legacy = QuestV0_1 {
+                id,
+                creator: owner.clone(),
+                name: "Legacy".to_string(),
+                description: "Stored by 0.1".to_string(),
+                reward_amount: Uint128::new(100),
+                completed: false,
+                completed_by: None,
+                created_at: 1,
+                completed_at: None,
+            };
+            QUESTS_V0_1.save(storage, id, &legacy).unwrap();
+            USER_QUESTS_V0_1.save(storage, owner, &vec![id]).unwrap();
         }
+        BALANCES
+            .save(
+                storage,
+                &creator,
+                &UserBalance {
+                    address: creator.clone(),
+                    balance: Uint128::new(500),
+                    total_earned: Uint128::zero(),
+                    quests_created: 1,
+                    quests_completed: 0,
+                },
+            )
+            .unwrap();
     }
-    Ok(quests)
-}
 
+    #[test]
+    fn test_migrate_from_legacy_layout() {
+        let creator = Addr::unchecked("creator");
+        let msg = MigrateMsg { denom: Some("uatom".to_string()) };
 
-fn query_user_stats(deps: Deps, address: String) -> StdResult<GetUserStatsResponse> {
-    let addr = deps.api.addr_validate(&address)?;
-    let balance = get_or_create_balance(deps.storage, &addr)?;
+        let mut deps = mock_dependencies_with_balance(&coins(499, "uatom"));
+        save_legacy_state(deps.as_mut().storage);
+        let result = migrate(deps.as_mut(), mock_env(), msg.clone());
+        assert_eq!(
+            result.unwrap_err(),
+            ContractError::InsufficientBalance {
+                required: Uint128::new(500),
+                available: Uint128::new(499),
+            },
+            "Legacy balances must be backed by coins before migrating"
+        );
 
-    Ok(GetUserStatsResponse {
-        address: addr,
-        balance: balance.balance,
-        total_earned: balance.total_earned,
-        quests_created: balance.quests_created,
-        quests_completed: balance.quests_completed,
-    })
-}
+        let mut deps = mock_dependencies_with_balance(&coins(500, "uatom"));
+        save_legacy_state(deps.as_mut().storage);
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
 
+        let funded = QUESTS.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(funded.status, QuestStatus::Open);
+        assert_eq!(ESCROW.load(deps.as_ref().storage, 1).unwrap(), Uint128::new(100));
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::{Addr, Uint128};
-    use cw_multi_test::{App, ContractWrapper, Executor};
+        let unfunded = QUESTS.load(deps.as_ref().storage, 2).unwrap();
+        assert_eq!(unfunded.status, QuestStatus::Cancelled);
 
-    fn setup_contract_for_user(user: &str) -> (App, Addr, Addr) {
-        let mut app = App::default();
-        let code = ContractWrapper::new(execute, instantiate, query);
-        let code_id = app.store_code(Box::new(code));
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(config.owner, Some(creator.clone()));
+        assert_eq!(config.arbiter, creator);
+        assert_eq!(config.denom, "uatom");
 
-        let user_addr = Addr::unchecked(user);
+        let token_info = TOKEN_INFO.load(deps.as_ref().storage).unwrap();
+        assert_eq!(token_info.total_supply, Uint128::new(500));
 
-        let msg = InstantiateMsg {
-            owner: None,
-            quest_creation_fee: "5".to_string(),
-            initial_balance: "1000".to_string(),
-        };
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+    }
 
-        let contract_addr = app
-            .instantiate_contract(code_id, user_addr.clone(), &msg, &[], "quest-contract", None)
-            .unwrap();
+    #[test]
+    fn test_migrate_refuses_downgrade() {
+        let mut deps = mock_dependencies();
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
 
-        (app, contract_addr, user_addr)
+        let result = migrate(deps.as_mut(), mock_env(), MigrateMsg { denom: None });
+        assert_eq!(
+            result.unwrap_err(),
+            ContractError::CannotDowngrade {
+                stored: "99.0.0".to_string(),
+                current: CONTRACT_VERSION.to_string(),
+            },
+            "Downgrades must be rejected"
+        );
     }
 
     #[test]
-    fn test_instantiate() {
-        let (_, _, _) = setup_contract_for_user("creator");
+    fn test_two_step_ownership_transfer() {
+        let (mut app, contract_addr, owner) = setup_contract_for_user("creator");
+        let multisig = Addr::unchecked("multisig");
+
+        let expiry = Expiration::AtHeight(app.block_info().height + 10);
+        let msg = ExecuteMsg::ProposeNewOwner {
+            owner: multisig.to_string(),
+            expiry: Some(expiry),
+        };
+        let result = app.execute_contract(multisig.clone(), contract_addr.clone(), &msg, &[]);
+        assert_eq!(contract_error(result), ContractError::Unauthorized {}, "Only the owner may propose");
+
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let config: GetConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(config.owner, Some(owner.clone()));
+        assert_eq!(config.pending_owner.unwrap().owner, multisig);
+
+        let result = app.execute_contract(
+            Addr::unchecked("stranger"),
+            contract_addr.clone(),
+            &ExecuteMsg::AcceptOwnership {},
+            &[],
+        );
+        assert_eq!(contract_error(result), ContractError::Unauthorized {}, "Only the pending owner may accept");
+
+        app.execute_contract(
+            multisig.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::AcceptOwnership {},
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            multisig.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::RenounceOwnership {},
+            &[],
+        )
+        .unwrap();
+
+        let config: GetConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(config.owner, None);
+        assert!(config.pending_owner.is_none());
     }
 
     #[test]
-    fn test_create_quest() {
-        let (mut app, contract_addr, user) = setup_contract_for_user("creator");
+    fn test_expired_ownership_proposal_cannot_be_accepted() {
+        let (mut app, contract_addr, owner) = setup_contract_for_user("creator");
+        let multisig = Addr::unchecked("multisig");
 
-        let msg = ExecuteMsg::CreateQuest {
-            name: "Test Quest".to_string(),
-            description: "A test quest".to_string(),
-            reward_amount: "100".to_string(),
+        let msg = ExecuteMsg::ProposeNewOwner {
+            owner: multisig.to_string(),
+            expiry: Some(Expiration::AtHeight(app.block_info().height + 1)),
         };
+        app.execute_contract(owner, contract_addr.clone(), &msg, &[])
+            .unwrap();
 
-        let result = app.execute_contract(user.clone(), contract_addr.clone(), &msg, &[]);
-        assert!(result.is_ok(), "Failed to create quest");
+        app.update_block(|block| block.height += 5);
 
-        let balance_query = QueryMsg::GetBalance {
-            address: user.to_string(),
-        };
-        let balance: GetBalanceResponse =
-            app.wrap().query_wasm_smart(&contract_addr, &balance_query).unwrap();
+        let result = app.execute_contract(
+            multisig,
+            contract_addr,
+            &ExecuteMsg::AcceptOwnership {},
+            &[],
+        );
+        assert_eq!(
+            contract_error(result),
+            ContractError::OwnershipProposalExpired {},
+            "Expired proposal must be rejected"
+        );
+    }
 
+    #[test]
+    fn test_error_codes_are_stable() {
+        let err = ContractError::InsufficientBalance {
+            required: Uint128::new(10),
+            available: Uint128::new(3),
+        };
+        assert_eq!(err.code(), "E200");
         assert_eq!(
-            balance.balance, Uint128::new(995),
-            "Balance should be 995 after quest creation"
+            err.to_string(),
+            "[E200] Insufficient balance: required 10, available 3"
         );
+
+        assert_eq!(ContractError::Unauthorized {}.code(), "E100");
+        assert_eq!(ContractError::QuestNotFound { quest_id: 7 }.to_string(), "[E300] Quest 7 not found");
+    }
+
+    fn cw721_instantiate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: cw721_base::InstantiateMsg,
+    ) -> StdResult<Response> {
+        Cw721MetadataContract::default().instantiate(deps, env, info, msg)
+    }
+
+    fn cw721_execute(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: cw721_metadata_onchain::ExecuteMsg,
+    ) -> Result<Response, cw721_base::ContractError> {
+        Cw721MetadataContract::default().execute(deps, env, info, msg)
+    }
+
+    fn cw721_query(deps: Deps, env: Env, msg: cw721_metadata_onchain::QueryMsg) -> StdResult<Binary> {
+        Cw721MetadataContract::default().query(deps, env, msg)
+    }
+
+    #[test]
+    fn test_badge_minted_on_completion_and_failures_do_not_block_payout() {
+        let (mut app, contract_addr, creator) = setup_contract_for_user("creator");
+
+        let badges = ContractWrapper::new(cw721_execute, cw721_instantiate, cw721_query);
+        let badges_id = app.store_code(Box::new(badges));
+        let mut badge_contract = |minter: &str| {
+            app.instantiate_contract(
+                badges_id,
+                creator.clone(),
+                &cw721_base::InstantiateMsg {
+                    name: "Quest Badges".to_string(),
+                    symbol: "BADGE".to_string(),
+                    minter: minter.to_string(),
+                },
+                &[],
+                "badges",
+                None,
+            )
+            .unwrap()
+        };
+        let working = badge_contract(contract_addr.as_str());
+        let misconfigured = badge_contract("someone-else");
+
+        let use_badges = |contract: &Addr| {
+            ExecuteMsg::UpdateConfig(UpdateConfigMsg {
+                badge_nft_contract: Some(contract.to_string()),
+                ..Default::default()
+            })
+        };
+        let badge_quest = ExecuteMsg::CreateQuest(CreateQuestMsg {
+            name: "Badge quest".to_string(),
+            description: "Earns a badge".to_string(),
+            reward_amount: "40".to_string(),
+            badge: Some(BadgeTemplate {
+                name: "Helper".to_string(),
+                description: None,
+                image: Some("ipfs://badge.png".to_string()),
+                token_uri: None,
+            }),
+            ..Default::default()
+        });
+        let complete = |app: &mut App, quest_id: u64, completer: &str| {
+            app.execute_contract(
+                Addr::unchecked(completer),
+                contract_addr.clone(),
+                &ExecuteMsg::SubmitCompletion { quest_id, proof: "ipfs://proof".to_string() },
+                &[],
+            )
+            .unwrap();
+            app.execute_contract(
+                creator.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::ApproveSubmission { quest_id, submitter: completer.to_string() },
+                &[],
+            )
+            .unwrap()
+        };
+
+        app.execute_contract(creator.clone(), contract_addr.clone(), &use_badges(&working), &[])
+            .unwrap();
+        app.execute_contract(creator.clone(), contract_addr.clone(), &badge_quest, &[])
+            .unwrap();
+        let res = complete(&mut app, 1, "alice");
+        assert!(res.events.iter().any(|e| e.ty == "wasm-badge_minted"));
+
+        let owner: OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &working,
+                &cw721_metadata_onchain::QueryMsg::OwnerOf {
+                    token_id: "1-alice".to_string(),
+                    include_expired: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(owner.owner, "alice");
+
+        let nft: NftInfoResponse<cw721_metadata_onchain::Extension> = app
+            .wrap()
+            .query_wasm_smart(
+                &working,
+                &cw721_metadata_onchain::QueryMsg::NftInfo { token_id: "1-alice".to_string() },
+            )
+            .unwrap();
+        let metadata = nft.extension.unwrap();
+        assert_eq!(metadata.name.as_deref(), Some("Helper"));
+        assert_eq!(metadata.image.as_deref(), Some("ipfs://badge.png"));
+        assert_eq!(metadata.attributes.unwrap()[0].value, "1");
+
+        app.execute_contract(creator.clone(), contract_addr.clone(), &use_badges(&misconfigured), &[])
+            .unwrap();
+        app.execute_contract(creator.clone(), contract_addr.clone(), &badge_quest, &[])
+            .unwrap();
+        let res = complete(&mut app, 2, "bob");
+        assert!(res.events.iter().any(|e| e.ty == "wasm-badge_mint_failed"));
+
+        let balance: GetBalanceResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetBalance { address: "bob".to_string() })
+            .unwrap();
+        assert_eq!(balance.balance, Uint128::new(40), "Reward is paid even though the mint failed");
     }
-}
\ No newline at end of file
+}